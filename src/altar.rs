@@ -0,0 +1,256 @@
+use bevy::{prelude::*, reflect::TypeUuid};
+use bevy_rapier2d::prelude::*;
+use iyes_loopless::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    consts::*,
+    essence::{EssenceCapacity, EssenceCounts},
+    level::Altar,
+    player::Player,
+    ron_asset::{AddRonResource, RonAsset, RonResource},
+    Element, GameState,
+};
+
+/// Trade of `cost` essence of one element for one essence of another
+#[derive(Deserialize, Clone)]
+pub struct Transmutation {
+    pub from: Element,
+    pub to: Element,
+    pub cost: u32,
+}
+
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "e4a1c6f2-8d3b-4e97-a215-6b0f9d7c2e58"]
+pub struct TransmutationList {
+    pub trades: Vec<Transmutation>,
+}
+
+impl RonAsset for TransmutationList {
+    const EXTENSIONS: &'static [&'static str] = &["trades.ron"];
+}
+
+/// Essence trades offered at altars
+pub struct Transmutations {
+    handle: Handle<TransmutationList>,
+    trades: Vec<Transmutation>,
+}
+
+impl Transmutations {
+    pub fn get(&self, index: usize) -> Option<&Transmutation> {
+        self.trades.get(index)
+    }
+}
+
+impl FromWorld for Transmutations {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        Transmutations {
+            handle: assets.load("altars/default.trades.ron"),
+            trades: Vec::new(),
+        }
+    }
+}
+
+impl RonResource for Transmutations {
+    type Asset = TransmutationList;
+
+    fn handle(&self) -> &Handle<TransmutationList> {
+        &self.handle
+    }
+
+    fn reload(&mut self, list: &TransmutationList) {
+        self.trades = list.trades.clone();
+        for trade in &self.trades {
+            if trade.from == trade.to || trade.cost == 0 {
+                warn!(
+                    "Transmutation of {} {:?} into {:?} does nothing",
+                    trade.cost, trade.from, trade.to
+                );
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+pub struct AltarPanel;
+
+/// Whether the player is standing on an altar with its trade panel open
+#[derive(Default, Deref, DerefMut)]
+pub struct AltarOpen(bool);
+
+#[derive(Component)]
+pub struct TradeButton(usize);
+
+pub struct Plugin;
+
+impl Plugin {
+    fn init(mut cmd: Commands) {
+        cmd.spawn_bundle(NodeBundle {
+            style: Style {
+                display: Display::None,
+                flex_direction: FlexDirection::ColumnReverse,
+                position: UiRect {
+                    right: Val::Px(20.0),
+                    top: Val::Px(60.0),
+                    ..default()
+                },
+                position_type: PositionType::Absolute,
+                padding: UiRect::all(Val::Px(4.0)),
+                ..default()
+            },
+            color: Color::rgba(0.2, 0.2, 0.2, 0.75).into(),
+            ..default()
+        })
+        .insert(AltarPanel);
+    }
+
+    fn update_panel(
+        mut cmd: Commands,
+        mut q_panel: Query<(Entity, &mut Style), With<AltarPanel>>,
+        q_player: Query<Entity, With<Player>>,
+        q_altars: Query<Entity, With<Altar>>,
+        rapier_ctx: Res<RapierContext>,
+        assets: Res<AssetServer>,
+        transmutations: Res<Transmutations>,
+        mut altar_open: ResMut<AltarOpen>,
+    ) {
+        let (panel, mut style) = match q_panel.get_single_mut() {
+            Ok(v) => v,
+            Err(_) => return,
+        };
+        let open = match q_player.get_single() {
+            Ok(player) => q_altars
+                .iter()
+                .any(|altar| rapier_ctx.intersection_pair(player, altar) == Some(true)),
+            Err(_) => false,
+        };
+
+        if open == **altar_open {
+            return;
+        }
+        **altar_open = open;
+        style.display = if open { Display::Flex } else { Display::None };
+        if !open {
+            return;
+        }
+
+        // Rebuilt every time the altar is stepped on so edits to the table show up
+        cmd.entity(panel).despawn_descendants();
+        cmd.entity(panel).with_children(|panel| {
+            for (i, trade) in transmutations.trades.iter().enumerate() {
+                panel
+                    .spawn_bundle(ButtonBundle {
+                        style: Style {
+                            padding: UiRect::all(Val::Px(4.0)),
+                            margin: UiRect::all(Val::Px(2.0)),
+                            ..default()
+                        },
+                        ..default()
+                    })
+                    .insert(TradeButton(i))
+                    .with_children(|button| {
+                        button.spawn_bundle(TextBundle {
+                            text: Text {
+                                sections: vec![
+                                    TextSection {
+                                        value: format!("{} {:?}", trade.cost, trade.from),
+                                        style: TextStyle {
+                                            font: assets.load("fonts/DejaVuSansMono.ttf"),
+                                            font_size: ALTAR_FONT_SIZE,
+                                            color: trade.from.color(),
+                                        },
+                                    },
+                                    TextSection {
+                                        value: " -> ".to_string(),
+                                        style: TextStyle {
+                                            font: assets.load("fonts/DejaVuSansMono.ttf"),
+                                            font_size: ALTAR_FONT_SIZE,
+                                            color: Color::WHITE,
+                                        },
+                                    },
+                                    TextSection {
+                                        value: format!("1 {:?}", trade.to),
+                                        style: TextStyle {
+                                            font: assets.load("fonts/DejaVuSansMono.ttf"),
+                                            font_size: ALTAR_FONT_SIZE,
+                                            color: trade.to.color(),
+                                        },
+                                    },
+                                ],
+                                ..default()
+                            },
+                            ..default()
+                        });
+                    });
+            }
+        });
+    }
+
+    fn update_buttons(
+        mut q_buttons: Query<(&TradeButton, &mut UiColor)>,
+        transmutations: Res<Transmutations>,
+        counts: Res<EssenceCounts>,
+        capacity: Res<EssenceCapacity>,
+    ) {
+        for (button, mut color) in &mut q_buttons {
+            let trade = match transmutations.get(button.0) {
+                Some(v) => v,
+                None => continue,
+            };
+            *color = if counts[trade.from] >= trade.cost && counts[trade.to] < capacity[trade.to] {
+                Color::DARK_GRAY.into()
+            } else {
+                Color::rgba(0.1, 0.1, 0.1, 0.75).into()
+            };
+        }
+    }
+
+    fn handle_trade_click(
+        q_buttons: Query<(&Interaction, &TradeButton), Changed<Interaction>>,
+        mouse: Res<Input<MouseButton>>,
+        transmutations: Res<Transmutations>,
+        mut counts: ResMut<EssenceCounts>,
+        capacity: Res<EssenceCapacity>,
+    ) {
+        if mouse.just_released(MouseButton::Left) {
+            for (interaction, button) in &q_buttons {
+                if interaction != &Interaction::Hovered {
+                    continue;
+                }
+                let trade = match transmutations.get(button.0) {
+                    Some(v) => v,
+                    None => continue,
+                };
+                if counts[trade.from] >= trade.cost && counts[trade.to] < capacity[trade.to] {
+                    *counts.get_mut(&trade.from).unwrap() -= trade.cost;
+                    *counts.get_mut(&trade.to).unwrap() += 1;
+                }
+            }
+        }
+    }
+
+    fn cleanup(
+        mut cmd: Commands,
+        q_panel: Query<Entity, With<AltarPanel>>,
+        mut altar_open: ResMut<AltarOpen>,
+    ) {
+        for entity in &q_panel {
+            cmd.entity(entity).despawn_recursive();
+        }
+        // The panel comes back hidden, so it must not be remembered as open
+        **altar_open = false;
+    }
+}
+
+impl bevy::app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_ron_resource::<Transmutations>()
+            .add_enter_system(GameState::InGame, Self::init)
+            .add_exit_system(GameState::InGame, Self::cleanup)
+            .add_system(Self::update_panel.run_in_state(GameState::InGame))
+            .add_system(Self::update_buttons.run_in_state(GameState::InGame))
+            .add_system(Self::handle_trade_click.run_in_state(GameState::InGame))
+            .init_resource::<AltarOpen>();
+    }
+}
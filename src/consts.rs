@@ -4,29 +4,105 @@ pub const WALL_COLLISION_GROUP: u32 = 1 << 2;
 pub const PLAYER_ATTACK_COLLISION_GROUP: u32 = 1 << 3;
 pub const ENEMY_ATTACK_COLLISION_GROUP: u32 = 1 << 4;
 pub const ESSENCE_COLLISION_GROUP: u32 = 1 << 5;
+/// Everything a hitbox can land on; its `Faction` decides which of them it hurts
+pub const HITBOX_TARGET_COLLISION_GROUPS: u32 = PLAYER_COLLISION_GROUP | ENEMY_COLLISION_GROUP;
 
 pub const PLAYER_SPEED: f32 = 100.0;
 pub const PLAYER_KICK_RANGE: f32 = 8.0;
 pub const PLAYER_KICK_FORCE: f32 = 100.0;
 pub const PLAYER_KICK_HITSTUN_SECS: f32 = 1.0;
+pub const PLAYER_IFRAME_TIME: f32 = 0.6;
+pub const IFRAME_BLINK_PERIOD: f32 = 0.05;
+pub const PLAYER_SHIELD: f32 = 0.0;
+
+pub const PLAYER_HEALTH_REGEN: f32 = 2.0;
+pub const HEALTH_REGEN_DELAY: f32 = 5.0;
+pub const HEALTH_REGEN_TICK: f32 = 1.0;
+pub const HEALTH_PICKUP_AMOUNT: f32 = 50.0;
+
+pub const SHIELD_REGEN_DELAY: f32 = 3.0;
+pub const SHIELD_REGEN_RATE: f32 = 10.0;
+
+pub const DAMAGE_NUMBER_LIFETIME: f32 = 0.8;
+pub const DAMAGE_NUMBER_RISE_SPEED: f32 = 20.0;
+pub const DAMAGE_NUMBER_OFFSET: f32 = 12.0;
+pub const DAMAGE_NUMBER_SPREAD: f32 = 8.0;
+pub const DAMAGE_NUMBER_DIGIT_WIDTH: f32 = 6.0;
+pub const DAMAGE_NUMBER_Z: f32 = 50.0;
+pub const DAMAGE_NUMBER_DAMAGE_COLOR: (u8, u8, u8) = (255, 255, 255);
+pub const DAMAGE_NUMBER_HEAL_COLOR: (u8, u8, u8) = (96, 232, 96);
+pub const DAMAGE_NUMBER_STATUS_COLOR: (u8, u8, u8) = (245, 160, 60);
+
+pub const SCREEN_SHAKE_PER_DAMAGE: f32 = 0.02;
+pub const SCREEN_SHAKE_PER_KNOCKBACK: f32 = 0.001;
+pub const SCREEN_SHAKE_MAX: f32 = 1.0;
+pub const SCREEN_SHAKE_DECAY: f32 = 2.5;
+pub const SCREEN_SHAKE_OFFSET: f32 = 6.0;
+pub const HIT_SPARK_SIZE: f32 = 3.0;
+pub const HIT_SPARK_DAMAGE_SCALE: f32 = 0.05;
+pub const HIT_SPARK_MAX_SIZE: f32 = 10.0;
+pub const HIT_SPARK_STATUS_SCALE: f32 = 1.5;
+pub const HIT_SPARK_LIFETIME: f32 = 0.08;
+pub const HIT_SPARK_Z: f32 = 40.0;
 
 pub const PLAYER_IDLE_ANIM_OFFSET: usize = 0;
 pub const PLAYER_WALK_ANIM_OFFSET: usize = 4;
 pub const PLAYER_WALK_ANIM_FRAMES: usize = 4;
 pub const PLAYER_KICK_ANIM_OFFSET: usize = 20;
 
+/// How far ahead of the player a potion aimed with a gamepad lands
+pub const GAMEPAD_THROW_DISTANCE: f32 = 64.0;
+pub const GAMEPAD_AIM_DEADZONE: f32 = 0.25;
+
 pub const CAMERA_PAN_SPEED: f32 = 20.0;
 pub const CAMERA_PAN_RANGE: f32 = 16.0;
 pub const CAMERA_PAN_SCALE: f32 = 0.0625;
 
+pub const ESSENCE_CAPACITY: u32 = 5;
+/// Two counter digits fit on the brew wheel
+pub const ESSENCE_MAX_CAPACITY: u32 = 99;
+pub const POUCH_UPGRADE_AMOUNT: u32 = 1;
+pub const ESSENCE_COUNTER_DIGITS: u32 = 2;
+pub const ESSENCE_COUNTER_DIGIT_WIDTH: f32 = 6.0;
+/// Essences knocked out by the first kick of an enemy without a drop table
+pub const ESSENCE_EXTRACT_COUNT: (u32, u32) = (2, 4);
+pub const ESSENCE_HEAL: f32 = 10.0;
+pub const ESSENCE_OVERFLOW_HEAL: f32 = 20.0;
+pub const ESSENCE_OVERFLOW_SCORE: u32 = 10;
+/// How long expiring essence blinks before it disappears
+pub const ESSENCE_BLINK_TIME: f32 = 2.0;
+pub const ESSENCE_BLINK_FRAME_TIME: f32 = 0.025;
+pub const ESSENCE_MAGNET_RADIUS: f32 = 24.0;
+pub const ESSENCE_MAGNET_SPEED: f32 = 60.0;
+pub const MAGNET_UPGRADE_AMOUNT: f32 = 8.0;
+
+pub const BREW_SLOTS: usize = 2;
+pub const HOTBAR_SLOTS: usize = 3;
+
 pub const BREW_UI_SIZE: f32 = 128.0;
 pub const BREW_UI_DEADZONE: f32 = 16.0;
 
 pub const BREW_UI_ICON_SIZE: f32 = 8.0;
 pub const BREW_UI_ICON_DISTANCE: f32 = 32.0;
 
+pub const PREVIEW_DOT_COUNT: usize = 64;
+pub const PREVIEW_DOT_SIZE: f32 = 2.0;
+pub const PREVIEW_DOT_SPACING: f32 = 8.0;
+pub const PREVIEW_ALPHA: f32 = 0.5;
+pub const PREVIEW_Z: f32 = 998.0;
+pub const PREVIEW_ICON_SIZE: f32 = 12.0;
+pub const PREVIEW_FONT_SIZE: f32 = 32.0;
+pub const PREVIEW_TEXT_SCALE: f32 = 0.25;
+
+pub const ALTAR_FONT_SIZE: f32 = 24.0;
+
 pub const POTION_THROW_SPEED: f32 = 400.0;
 pub const POTION_SPIN_SPEED: f32 = 32.0;
+pub const POTION_MAX_BOUNCES: u32 = 3;
+pub const POTION_BOUNCE_PROBE: f32 = 8.0;
+pub const POTION_MAX_TIER: usize = 3;
+pub const POTION_FUSE_TIME: f32 = 3.0;
+pub const POTION_FUSE_COUNTDOWN_OFFSET: f32 = 8.0;
 
 pub const GRID_SIZE: i32 = 16;
 
@@ -42,6 +118,21 @@ pub const ELEMENTAL_ATTACK_EMIT_FRAME: usize = 7;
 
 pub const ELEMENTAL_DEATH_ANIM_OFFSET: usize = 10;
 pub const ELEMENTAL_DEATH_ANIM_FRAMES: usize = 4;
+pub const ELEMENTAL_SPRITE_HEIGHT: f32 = 32.0;
+
+pub const ENEMY_HEALTH_BAR_WIDTH: f32 = 16.0;
+pub const ENEMY_HEALTH_BAR_HEIGHT: f32 = 2.0;
+pub const ENEMY_HEALTH_BAR_GAP: f32 = 2.0;
+/// Docked bar widths are percentages of the screen
+pub const ELITE_HEALTH_BAR_WIDTH: f32 = 30.0;
+pub const ELITE_HEALTH_BAR_HEIGHT: f32 = 10.0;
+pub const BOSS_HEALTH_BAR_WIDTH: f32 = 50.0;
+pub const BOSS_HEALTH_BAR_HEIGHT: f32 = 16.0;
+
+/// Damage multiplier for an elemental hit by its own element
+pub const ELEMENTAL_RESISTANCE: f32 = 0.5;
+/// Damage multiplier for an elemental hit by the element that counters it
+pub const ELEMENTAL_WEAKNESS: f32 = 1.5;
 
 pub const FIRE_ELEMENTAL_SPEED: f32 = 75.0;
 pub const FIRE_ELEMENTAL_HEALTH: f32 = 100.0;
@@ -93,81 +184,8 @@ pub const EARTH_ELEMENTAL_ATTACK_PERIOD: f32 = 1.7;
 pub const EARTH_ELEMENTAL_ANIM_PERIOD: f32 = 0.1;
 pub const EARTH_ELEMENTAL_ATTACK_VELOCITY: f32 = 100.0;
 pub const EARTH_ELEMENTAL_ATTACK_DAMAGE: f32 = 30.0;
+pub const EARTH_ELEMENTAL_ARMOR: f32 = 0.25;
 
-pub const FIRE_FIRE_RADIUS: f32 = 32.0;
-pub const FIRE_FIRE_IMPULSE: f32 = 25.0;
-pub const FIRE_FIRE_DAMAGE: f32 = 60.0;
-pub const FIRE_FIRE_HITSTUN: f32 = 0.5;
-
-pub const WATER_WATER_SPEED: f32 = 100.0;
-pub const WATER_WATER_DURATION: f32 = 0.6;
-pub const WATER_WATER_HALF_WIDTH: f32 = 32.0;
-pub const WATER_WATER_HALF_HEIGHT: f32 = 8.0;
-pub const WATER_WATER_FORCE: f32 = 15.0;
-
-pub const WIND_WIND_DURATION: f32 = 2.0;
-pub const WIND_WIND_RADIUS: f32 = 32.0;
-pub const WIND_WIND_FORCE: f32 = 5.0;
-pub const WIND_WIND_MAX_FALLOFF: f32 = 0.5;
-pub const WIND_WIND_FALLOFF_START: f32 = 5.0;
-pub const WIND_WIND_FALLOFF_END: f32 = 32.0;
-
-pub const LIGHTNING_LIGHTNING_RADIUS: f32 = 8.0;
-pub const LIGHTNING_LIGHTNING_HITSTUN: f32 = 3.0;
-pub const LIGHTNING_LIGHTNING_DAMAGE: f32 = 250.0;
-pub const LIGHTNING_LIGHTNING_MAX_FALLOFF: f32 = 0.1;
-pub const LIGHTNING_LIGHTNING_FALLOFF_START: f32 = 2.0;
-pub const LIGHTNING_LIGHTNING_FALLOFF_END: f32 = 8.0;
-
-pub const EARTH_EARTH_RADIUS: f32 = 16.0;
-pub const EARTH_EARTH_DURATION: f32 = 2.75;
-
-pub const FIRE_WATER_DURATION: f32 = 2.0;
-pub const FIRE_WATER_RADIUS: f32 = 32.0;
-pub const FIRE_WATER_FORCE: f32 = 5.0;
-pub const FIRE_WATER_MAX_FALLOFF: f32 = 0.5;
-pub const FIRE_WATER_FALLOFF_START: f32 = 5.0;
-pub const FIRE_WATER_FALLOFF_END: f32 = 32.0;
-
-pub const FIRE_WIND_RADIUS: f32 = 48.0;
-pub const FIRE_WIND_DURATION: f32 = 0.1;
-
-pub const FIRE_LIGHTNING_RADIUS: f32 = 4.0;
-pub const FIRE_LIGHTNING_DURATION: f32 = 0.15;
-
-pub const FIRE_EARTH_RADIUS: f32 = 48.0;
-pub const FIRE_EARTH_DAMAGE: f32 = 10.0;
-pub const FIRE_EARTH_TICK: f32 = 0.25;
-pub const FIRE_EARTH_DURATION: f32 = 5.0;
-
-pub const WATER_WIND_CHASE_SPEED: f32 = 10.0;
-pub const WATER_WIND_CHASE_RADIUS: f32 = 128.0;
-pub const WATER_WIND_DURATION: f32 = 3.0;
-pub const WATER_WIND_RADIUS: f32 = 48.0;
-
-pub const WATER_LIGHTNING_RADIUS: f32 = 48.0;
-pub const WATER_LIGHTNING_DURATION: f32 = 0.1;
-
-pub const WATER_EARTH_HALF_WIDTH: f32 = 16.0;
-pub const WATER_EARTH_HALF_HEIGHT: f32 = 48.0;
-pub const WATER_EARTH_HITSTUN: f32 = 0.5;
-pub const WATER_EARTH_DAMAGE: f32 = 25.0;
-pub const WATER_EARTH_TICK: f32 = 0.25;
-pub const WATER_EARTH_DURATION: f32 = 5.0;
-
-pub const WIND_LIGHTNING_CHASE_SPEED: f32 = 10.0;
-pub const WIND_LIGHTNING_CHASE_RADIUS: f32 = 128.0;
-pub const WIND_LIGHTNING_DURATION: f32 = 3.0;
-pub const WIND_LIGHTNING_RADIUS: f32 = 48.0;
-pub const WIND_LIGHTNING_DAMAGE: f32 = 5.0;
-pub const WIND_LIGHTNING_TICK: f32 = 0.3;
-
-pub const WIND_EARTH_RADIUS: f32 = 48.0;
-pub const WIND_EARTH_DURATION: f32 = 0.1;
-
-pub const LIGHTNING_EARTH_COUNT: u32 = 17;
-pub const LIGHTNING_EARTH_RADIUS: f32 = 2.0;
-pub const LIGHTNING_EARTH_SPEED: f32 = 600.0;
-pub const LIGHTNING_EARTH_HITSTUN: f32 = 1.5;
-pub const LIGHTNING_EARTH_DAMAGE: f32 = 5.0;
-pub const LIGHTNING_EARTH_DURATION: f32 = 0.3;
+pub const STATUS_EFFECT_DURATION: f32 = 5.0;
+pub const REGENERATING_TICK: f32 = 0.5;
+pub const REGENERATING_TICK_HEAL: f32 = 5.0;
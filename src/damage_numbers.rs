@@ -0,0 +1,141 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{
+    consts::*,
+    health::{DamageKind, DamageSource, HealthChanged},
+    level::NotFromLevel,
+    utils::{Digits, TimeScale},
+    GameState,
+};
+
+/// Whether damage and heal numbers pop up, toggled on the main menu
+#[derive(Deref, DerefMut)]
+pub struct DamageNumbers(pub bool);
+
+impl Default for DamageNumbers {
+    fn default() -> Self {
+        DamageNumbers(true)
+    }
+}
+
+/// Number rising and fading above something that was hit or healed
+#[derive(Component)]
+pub struct FloatingNumber {
+    timer: Timer,
+    color: Color,
+}
+
+pub struct Plugin;
+
+impl Plugin {
+    fn spawn_numbers(
+        mut cmd: Commands,
+        q_transform: Query<&GlobalTransform>,
+        mut event_reader: EventReader<HealthChanged>,
+        digits: Res<Digits>,
+        enabled: Res<DamageNumbers>,
+    ) {
+        for event in event_reader.iter() {
+            if !**enabled {
+                continue;
+            }
+            let value = event.amount.abs().round() as u32;
+            if value == 0 {
+                continue;
+            }
+            let transform = match q_transform.get(event.entity) {
+                Ok(v) => v,
+                Err(_) => continue,
+            };
+
+            let (r, g, b) = if event.amount > 0.0 {
+                DAMAGE_NUMBER_HEAL_COLOR
+            } else if matches!(
+                event.source,
+                Some(DamageSource {
+                    kind: DamageKind::StatusTick(_),
+                    ..
+                })
+            ) {
+                DAMAGE_NUMBER_STATUS_COLOR
+            } else {
+                DAMAGE_NUMBER_DAMAGE_COLOR
+            };
+            let color = Color::rgb_u8(r, g, b);
+            // Spread out a little so numbers landing together don't cover each other
+            let offset = Vec2::new(
+                (fastrand::f32() - 0.5) * DAMAGE_NUMBER_SPREAD,
+                DAMAGE_NUMBER_OFFSET,
+            );
+            let text = value.to_string();
+            let width = text.len();
+
+            cmd.spawn_bundle(SpatialBundle {
+                transform: Transform::from_translation(
+                    (transform.translation().truncate() + offset).extend(DAMAGE_NUMBER_Z),
+                ),
+                ..default()
+            })
+            .insert_bundle((
+                FloatingNumber {
+                    timer: Timer::from_seconds(DAMAGE_NUMBER_LIFETIME, false),
+                    color,
+                },
+                NotFromLevel,
+            ))
+            .with_children(|parent| {
+                for (i, digit) in text.bytes().enumerate() {
+                    parent.spawn_bundle(SpriteSheetBundle {
+                        texture_atlas: digits.clone_weak(),
+                        sprite: TextureAtlasSprite {
+                            index: (digit - b'0') as usize,
+                            color,
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(
+                            (i as f32 - (width - 1) as f32 / 2.0) * DAMAGE_NUMBER_DIGIT_WIDTH,
+                            0.0,
+                            0.0,
+                        ),
+                        ..default()
+                    });
+                }
+            });
+        }
+    }
+
+    fn update_numbers(
+        mut cmd: Commands,
+        mut q_numbers: Query<(Entity, &mut FloatingNumber, &mut Transform, &Children)>,
+        mut q_digits: Query<&mut TextureAtlasSprite>,
+        time: Res<Time>,
+        time_scale: Res<TimeScale>,
+    ) {
+        let delta = time.delta().mul_f32(**time_scale);
+        for (entity, mut number, mut transform, children) in &mut q_numbers {
+            number.timer.tick(delta);
+            if number.timer.finished() {
+                cmd.entity(entity).despawn_recursive();
+                continue;
+            }
+
+            transform.translation.y += DAMAGE_NUMBER_RISE_SPEED * delta.as_secs_f32();
+            let mut color = number.color;
+            color.set_a(1.0 - number.timer.percent());
+            for &child in children {
+                if let Ok(mut sprite) = q_digits.get_mut(child) {
+                    sprite.color = color;
+                }
+            }
+        }
+    }
+}
+
+impl bevy::app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DamageNumbers>()
+            .add_system(Self::spawn_numbers.run_in_state(GameState::InGame))
+            .add_system(Self::update_numbers.run_in_state(GameState::InGame));
+    }
+}
@@ -0,0 +1,155 @@
+use bevy::{prelude::*, reflect::TypeUuid, utils::HashMap};
+use serde::Deserialize;
+
+use crate::{
+    consts::*,
+    healing::HealthPickup,
+    health::DamageKind,
+    ron_asset::{AddRonResource, RonAsset, RonResource},
+    Element,
+};
+
+/// What dealt the last blow to an enemy
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KillMethod {
+    Kick,
+    Potion,
+    Status,
+}
+
+impl KillMethod {
+    pub fn of(kind: &DamageKind) -> Option<Self> {
+        match kind {
+            DamageKind::Kick => Some(KillMethod::Kick),
+            DamageKind::Potion(_) => Some(KillMethod::Potion),
+            DamageKind::StatusTick(_) => Some(KillMethod::Status),
+            DamageKind::EnemyAttack => None,
+        }
+    }
+}
+
+fn one() -> (u32, u32) {
+    (1, 1)
+}
+
+fn extract() -> (u32, u32) {
+    ESSENCE_EXTRACT_COUNT
+}
+
+#[derive(Deserialize, Clone)]
+pub struct EssenceDrop {
+    pub element: Element,
+    /// Inclusive range the number of essences is rolled from
+    #[serde(default = "one")]
+    pub count: (u32, u32),
+    /// Chance of dropping at all; always drops if unset
+    #[serde(default)]
+    pub chance: Option<f32>,
+    /// Only drops if the enemy was killed this way
+    #[serde(default)]
+    pub killed_by: Option<KillMethod>,
+}
+
+impl EssenceDrop {
+    /// How many essences this drop gives for an enemy killed by `method`
+    pub fn roll(&self, method: Option<KillMethod>) -> u32 {
+        if self.killed_by.is_some() && self.killed_by != method {
+            return 0;
+        }
+        if let Some(chance) = self.chance {
+            if fastrand::f32() >= chance {
+                return 0;
+            }
+        }
+        fastrand::u32(self.count.0..=self.count.1.max(self.count.0))
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct HealthDrop {
+    pub pickup: HealthPickup,
+    /// Chance of dropping at all; always drops if unset
+    #[serde(default)]
+    pub chance: Option<f32>,
+}
+
+impl HealthDrop {
+    pub fn roll(&self) -> bool {
+        self.chance.map_or(true, |chance| fastrand::f32() < chance)
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct DropTable {
+    /// Element of the elementals this table is for
+    pub enemy: Element,
+    #[serde(default)]
+    pub drops: Vec<EssenceDrop>,
+    /// Essence knocked out by the first kick, separate from what drops on death
+    #[serde(default = "extract")]
+    pub extract: (u32, u32),
+    #[serde(default)]
+    pub health: Option<HealthDrop>,
+}
+
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "9c2e4f71-3b8d-4a65-b0d2-7f1e5a9c3d48"]
+pub struct DropTableList {
+    pub tables: Vec<DropTable>,
+}
+
+impl RonAsset for DropTableList {
+    const EXTENSIONS: &'static [&'static str] = &["drops.ron"];
+}
+
+pub struct DropTables {
+    handle: Handle<DropTableList>,
+    tables: HashMap<Element, DropTable>,
+}
+
+impl DropTables {
+    pub fn get(&self, enemy: Element) -> Option<&DropTable> {
+        self.tables.get(&enemy)
+    }
+
+    /// Range of essences the first kick knocks out of an enemy
+    pub fn extract(&self, enemy: Element) -> (u32, u32) {
+        self.get(enemy)
+            .map_or(ESSENCE_EXTRACT_COUNT, |table| table.extract)
+    }
+}
+
+impl FromWorld for DropTables {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        DropTables {
+            handle: assets.load("enemies/default.drops.ron"),
+            tables: HashMap::new(),
+        }
+    }
+}
+
+impl RonResource for DropTables {
+    type Asset = DropTableList;
+
+    fn handle(&self) -> &Handle<DropTableList> {
+        &self.handle
+    }
+
+    fn reload(&mut self, list: &DropTableList) {
+        self.tables.clear();
+        for table in &list.tables {
+            if self.tables.insert(table.enemy, table.clone()).is_some() {
+                warn!("Duplicate drop table for {:?} elementals", table.enemy);
+            }
+        }
+    }
+}
+
+pub struct Plugin;
+
+impl bevy::app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_ron_resource::<DropTables>();
+    }
+}
@@ -1,6 +1,6 @@
 use bevy::utils::Duration;
 
-use bevy::{prelude::*, sprite::Anchor};
+use bevy::{prelude::*, sprite::Anchor, utils::HashMap};
 
 use bevy_ecs_ldtk::prelude::*;
 use bevy_ecs_ldtk::utils::grid_coords_to_translation_centered;
@@ -11,12 +11,24 @@ use iyes_loopless::prelude::*;
 use ordered_float::OrderedFloat;
 use pathfinding::directed::astar::astar;
 
+use crate::drops::{DropTables, KillMethod};
+use crate::essence::spawn_essence;
+use crate::healing::spawn_health_pickup;
+use crate::health::Armor;
+use crate::health::DamageKind;
+use crate::health::DamageSource;
 use crate::health::Dead;
+use crate::health::Died;
 use crate::health::Health;
+use crate::health::Resistances;
+use crate::hitbox::DamageElement;
 use crate::hitbox::DamageOnce;
 use crate::hitbox::DamagePeriodic;
+use crate::hitbox::Faction;
 use crate::hitbox::Falloff;
+use crate::hitbox::HitSource;
 use crate::hitbox::Hitbox;
+use crate::hitbox::IgnoreIFrames;
 use crate::hitstun::HitstunTimer;
 use crate::level::NotFromLevel;
 use crate::level::WalkableTiles;
@@ -54,6 +66,30 @@ pub enum EnemyState {
     Attack,
 }
 
+/// Elementals shrug off their own element and are weak to the one that counters it
+fn elemental_resistances(element: Element) -> Resistances {
+    let weakness = match element {
+        Element::Fire => Element::Water,
+        Element::Water => Element::Lightning,
+        Element::Wind => Element::Fire,
+        Element::Lightning => Element::Earth,
+        Element::Earth => Element::Wind,
+    };
+    Resistances::new([
+        (element, ELEMENTAL_RESISTANCE),
+        (weakness, ELEMENTAL_WEAKNESS),
+    ])
+}
+
+/// How tough an enemy is meant to be, set per entity in LDtk
+#[derive(Component, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Rank {
+    #[default]
+    Normal,
+    Elite,
+    Boss,
+}
+
 #[derive(Bundle)]
 pub struct ElementalBundle {
     enemy: Enemy,
@@ -66,9 +102,12 @@ pub struct ElementalBundle {
     hitstun: HitstunTimer,
     anim: AnimationTimer,
     health: Health,
+    armor: Armor,
+    faction: Faction,
     state: EnemyState,
     attack_timer: AttackTimer,
     element: Element,
+    rank: Rank,
     stats: EnemyStats,
     attacked: Attacked,
     #[bundle]
@@ -85,7 +124,18 @@ impl LdtkEntity for ElementalBundle {
         texture_atlases: &mut Assets<TextureAtlas>,
     ) -> Self {
         let mut element = None;
+        let mut rank = Rank::Normal;
         for field in &entity_instance.field_instances {
+            if field.identifier.as_str() == "Rank" {
+                rank = match &field.value {
+                    FieldValue::Enum(Some(value)) => match value.as_str() {
+                        "Elite" => Rank::Elite,
+                        "Boss" => Rank::Boss,
+                        _ => Rank::Normal,
+                    },
+                    _ => Rank::Normal,
+                }
+            }
             if field.identifier.as_str() == "Element" {
                 element = match &field.value {
                     FieldValue::Enum(value) => match value.as_ref().unwrap().as_str() {
@@ -113,7 +163,8 @@ impl LdtkEntity for ElementalBundle {
                 filters: PLAYER_COLLISION_GROUP
                     | ENEMY_COLLISION_GROUP
                     | WALL_COLLISION_GROUP
-                    | PLAYER_ATTACK_COLLISION_GROUP,
+                    | PLAYER_ATTACK_COLLISION_GROUP
+                    | ENEMY_ATTACK_COLLISION_GROUP,
             },
             locked: LockedAxes::ROTATION_LOCKED,
             damping: Damping {
@@ -123,9 +174,12 @@ impl LdtkEntity for ElementalBundle {
             anim: AnimationTimer(Timer::from_seconds(0.0, true)),
             hitstun: HitstunTimer(Timer::from_seconds(0.0, false)),
             health: Health::new(0.0),
+            armor: Armor(0.0),
+            faction: Faction::Enemy,
             state: EnemyState::Idle,
             attack_timer: AttackTimer(Timer::from_seconds(0.0, false)),
             element,
+            rank,
             spritesheet: SpriteSheetBundle {
                 sprite: TextureAtlasSprite {
                     anchor: Anchor::Custom(Vec2::from_array([0.0, -0.25])),
@@ -145,7 +199,8 @@ impl LdtkEntity for ElementalBundle {
                 bundle
                     .attack_timer
                     .set_duration(Duration::from_secs_f32(FIRE_ELEMENTAL_ATTACK_PERIOD));
-                bundle.health = Health::new(FIRE_ELEMENTAL_HEALTH);
+                bundle.health = Health::new(FIRE_ELEMENTAL_HEALTH)
+                    .with_resistances(elemental_resistances(Element::Fire));
                 bundle.stats = EnemyStats {
                     speed: FIRE_ELEMENTAL_SPEED,
                     aggro_range: FIRE_ELEMENTAL_AGGRO_RANGE,
@@ -168,7 +223,8 @@ impl LdtkEntity for ElementalBundle {
                 bundle
                     .attack_timer
                     .set_duration(Duration::from_secs_f32(WATER_ELEMENTAL_ATTACK_PERIOD));
-                bundle.health = Health::new(WATER_ELEMENTAL_HEALTH);
+                bundle.health = Health::new(WATER_ELEMENTAL_HEALTH)
+                    .with_resistances(elemental_resistances(Element::Water));
                 bundle.stats = EnemyStats {
                     speed: WATER_ELEMENTAL_SPEED,
                     aggro_range: WATER_ELEMENTAL_AGGRO_RANGE,
@@ -191,7 +247,8 @@ impl LdtkEntity for ElementalBundle {
                 bundle
                     .attack_timer
                     .set_duration(Duration::from_secs_f32(WIND_ELEMENTAL_ATTACK_PERIOD));
-                bundle.health = Health::new(WIND_ELEMENTAL_HEALTH);
+                bundle.health = Health::new(WIND_ELEMENTAL_HEALTH)
+                    .with_resistances(elemental_resistances(Element::Wind));
                 bundle.stats = EnemyStats {
                     speed: WIND_ELEMENTAL_SPEED,
                     aggro_range: WIND_ELEMENTAL_AGGRO_RANGE,
@@ -214,7 +271,8 @@ impl LdtkEntity for ElementalBundle {
                 bundle
                     .attack_timer
                     .set_duration(Duration::from_secs_f32(LIGHTNING_ELEMENTAL_ATTACK_PERIOD));
-                bundle.health = Health::new(LIGHTNING_ELEMENTAL_HEALTH);
+                bundle.health = Health::new(LIGHTNING_ELEMENTAL_HEALTH)
+                    .with_resistances(elemental_resistances(Element::Lightning));
                 bundle.stats = EnemyStats {
                     speed: LIGHTNING_ELEMENTAL_SPEED,
                     aggro_range: LIGHTNING_ELEMENTAL_AGGRO_RANGE,
@@ -237,7 +295,9 @@ impl LdtkEntity for ElementalBundle {
                 bundle
                     .attack_timer
                     .set_duration(Duration::from_secs_f32(EARTH_ELEMENTAL_ATTACK_PERIOD));
-                bundle.health = Health::new(EARTH_ELEMENTAL_HEALTH);
+                bundle.health = Health::new(EARTH_ELEMENTAL_HEALTH)
+                    .with_resistances(elemental_resistances(Element::Earth));
+                bundle.armor = Armor(EARTH_ELEMENTAL_ARMOR);
                 bundle.stats = EnemyStats {
                     speed: EARTH_ELEMENTAL_SPEED,
                     aggro_range: EARTH_ELEMENTAL_AGGRO_RANGE,
@@ -351,7 +411,14 @@ impl Plugin {
     fn attack(
         mut cmd: Commands,
         mut q_enemy: Query<
-            (&Transform, &TextureAtlasSprite, &Element, &mut Attacked),
+            (
+                Entity,
+                &Transform,
+                &TextureAtlasSprite,
+                &Element,
+                &Faction,
+                &mut Attacked,
+            ),
             (With<Enemy>, Without<Player>, Changed<TextureAtlasSprite>),
         >,
         q_player: Query<&Transform, (Without<Enemy>, With<Player>)>,
@@ -364,7 +431,7 @@ impl Plugin {
         };
         let player_pos = player_transform.translation.truncate();
 
-        for (enemy_transform, sprite, element, mut attacked) in &mut q_enemy {
+        for (enemy, enemy_transform, sprite, element, faction, mut attacked) in &mut q_enemy {
             if sprite.index == ELEMENTAL_ATTACK_EMIT_FRAME && !attacked.0 {
                 attacked.0 = true;
                 let enemy_pos = enemy_transform.translation.truncate();
@@ -389,11 +456,17 @@ impl Plugin {
                             Collider::ball(FIRE_ELEMENTAL_ATTACK_RADIUS),
                             CollisionGroups {
                                 memberships: ENEMY_ATTACK_COLLISION_GROUP,
-                                filters: PLAYER_COLLISION_GROUP | WALL_COLLISION_GROUP,
+                                filters: HITBOX_TARGET_COLLISION_GROUPS | WALL_COLLISION_GROUP,
                             },
                             ActiveEvents::COLLISION_EVENTS,
                             Sensor,
                             Hitbox,
+                            DamageElement(Element::Fire),
+                            HitSource(DamageSource {
+                                entity: Some(enemy),
+                                kind: DamageKind::EnemyAttack,
+                            }),
+                            *faction,
                             DamageOnce::new(FIRE_ELEMENTAL_ATTACK_DAMAGE, Falloff::none()),
                             DestroyOnHit,
                             NotFromLevel,
@@ -422,11 +495,17 @@ impl Plugin {
                                 Collider::ball(2.0),
                                 CollisionGroups {
                                     memberships: ENEMY_ATTACK_COLLISION_GROUP,
-                                    filters: PLAYER_COLLISION_GROUP,
+                                    filters: HITBOX_TARGET_COLLISION_GROUPS,
                                 },
                                 ActiveEvents::COLLISION_EVENTS,
                                 Sensor,
                                 Hitbox,
+                                DamageElement(Element::Water),
+                                HitSource(DamageSource {
+                                    entity: Some(enemy),
+                                    kind: DamageKind::EnemyAttack,
+                                }),
+                                *faction,
                                 DamageOnce::new(WATER_ELEMENTAL_ATTACK_DAMAGE, Falloff::none()),
                                 DespawnTimer(Timer::from_seconds(5.0, false)),
                                 Spiral { rate: 2.0 },
@@ -452,11 +531,17 @@ impl Plugin {
                             Collider::ball(8.0),
                             CollisionGroups {
                                 memberships: ENEMY_ATTACK_COLLISION_GROUP,
-                                filters: PLAYER_COLLISION_GROUP,
+                                filters: HITBOX_TARGET_COLLISION_GROUPS,
                             },
                             ActiveEvents::COLLISION_EVENTS,
                             Sensor,
                             Hitbox,
+                            DamageElement(Element::Wind),
+                            HitSource(DamageSource {
+                                entity: Some(enemy),
+                                kind: DamageKind::EnemyAttack,
+                            }),
+                            *faction,
                             DamageOnce::new(WIND_ELEMENTAL_ATTACK_DAMAGE, Falloff::none()),
                             DespawnTimer(Timer::from_seconds(0.05, false)),
                             NotFromLevel,
@@ -498,11 +583,17 @@ impl Plugin {
                                     Collider::ball(16.0),
                                     CollisionGroups {
                                         memberships: ENEMY_ATTACK_COLLISION_GROUP,
-                                        filters: PLAYER_COLLISION_GROUP,
+                                        filters: HITBOX_TARGET_COLLISION_GROUPS,
                                     },
                                     ActiveEvents::COLLISION_EVENTS,
                                     Sensor,
                                     Hitbox,
+                                    DamageElement(Element::Lightning),
+                                    HitSource(DamageSource {
+                                        entity: Some(enemy),
+                                        kind: DamageKind::EnemyAttack,
+                                    }),
+                                    *faction,
                                     DamagePeriodic::new(
                                         LIGHTNING_ELEMENTAL_ATTACK_DAMAGE,
                                         Falloff::none(),
@@ -529,12 +620,19 @@ impl Plugin {
                             Collider::ball(16.0),
                             CollisionGroups {
                                 memberships: ENEMY_ATTACK_COLLISION_GROUP,
-                                filters: PLAYER_COLLISION_GROUP,
+                                filters: HITBOX_TARGET_COLLISION_GROUPS,
                             },
                             ActiveEvents::COLLISION_EVENTS,
                             Sensor,
                             Hitbox,
+                            DamageElement(Element::Earth),
+                            HitSource(DamageSource {
+                                entity: Some(enemy),
+                                kind: DamageKind::EnemyAttack,
+                            }),
+                            *faction,
                             DamageOnce::new(EARTH_ELEMENTAL_ATTACK_DAMAGE, Falloff::none()),
+                            IgnoreIFrames,
                             DespawnTimer(Timer::from_seconds(0.05, false)),
                             NotFromLevel,
                         ));
@@ -742,9 +840,39 @@ impl Plugin {
 
     fn start_die(
         mut cmd: Commands,
-        mut q_enemy: Query<(Entity, &mut TextureAtlasSprite), (With<Enemy>, Added<Dead>)>,
+        mut q_enemy: Query<
+            (Entity, &mut TextureAtlasSprite, &Element, &GlobalTransform),
+            (With<Enemy>, Added<Dead>),
+        >,
+        q_enemies: Query<(), With<Enemy>>,
+        mut event_reader: EventReader<Died>,
+        drop_tables: Res<DropTables>,
+        assets: Res<AssetServer>,
+        mut kill_methods: Local<HashMap<Entity, KillMethod>>,
     ) {
-        for (entity, mut sprite) in &mut q_enemy {
+        // Died is sent before the Dead marker is applied, so hold on to it until then
+        for died in event_reader.iter() {
+            let method = died
+                .source
+                .as_ref()
+                .and_then(|source| KillMethod::of(&source.kind));
+            if let (Some(method), true) = (method, q_enemies.contains(died.entity)) {
+                kill_methods.insert(died.entity, method);
+            }
+        }
+
+        for (entity, mut sprite, element, transform) in &mut q_enemy {
+            if let Some(table) = drop_tables.get(*element) {
+                for drop in &table.drops {
+                    let count = drop.roll(kill_methods.get(&entity).copied());
+                    spawn_essence(&mut cmd, drop.element, transform, count);
+                }
+                if let Some(drop) = table.health.as_ref().filter(|drop| drop.roll()) {
+                    spawn_health_pickup(&mut cmd, &assets, drop.pickup.clone(), transform);
+                }
+            }
+            kill_methods.remove(&entity);
+
             sprite.index = ELEMENTAL_DEATH_ANIM_OFFSET;
             cmd.entity(entity)
                 .remove::<Collider>()
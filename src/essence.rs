@@ -1,15 +1,20 @@
 use std::ops::Index;
 
-use bevy::{prelude::*, utils::HashMap};
+use bevy::{
+    prelude::*,
+    utils::{Duration, HashMap},
+};
+use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
 use iyes_loopless::prelude::*;
 
 use crate::{
     consts::*,
     health::HealthChange,
+    level::NotFromLevel,
     player::Player,
-    utils::{ElementIconAtlases, UniformAnim},
-    Element, GameState,
+    utils::{DespawnTimer, ElementIconAtlases, TimeScale, UniformAnim},
+    Element, GameMode, GameState, PauseState,
 };
 
 #[derive(Deref, DerefMut)]
@@ -35,9 +40,192 @@ impl Index<Element> for EssenceCounts {
     }
 }
 
+/// How much essence of each element the player can carry
+#[derive(Deref, DerefMut, Clone)]
+pub struct EssenceCapacity(HashMap<Element, u32>);
+
+impl Default for EssenceCapacity {
+    fn default() -> Self {
+        EssenceCapacity(HashMap::from([
+            (Element::Fire, ESSENCE_CAPACITY),
+            (Element::Water, ESSENCE_CAPACITY),
+            (Element::Wind, ESSENCE_CAPACITY),
+            (Element::Lightning, ESSENCE_CAPACITY),
+            (Element::Earth, ESSENCE_CAPACITY),
+        ]))
+    }
+}
+
+impl Index<Element> for EssenceCapacity {
+    type Output = u32;
+
+    fn index(&self, index: Element) -> &Self::Output {
+        &self.0[&index]
+    }
+}
+
+/// Distance from which essence is drawn toward the player
+#[derive(Deref, DerefMut, Clone)]
+pub struct EssenceMagnet(pub f32);
+
+impl Default for EssenceMagnet {
+    fn default() -> Self {
+        EssenceMagnet(ESSENCE_MAGNET_RADIUS)
+    }
+}
+
+/// Upgrades the current level was entered with, restored when it is restarted
+#[derive(Default)]
+pub struct LevelStartUpgrades {
+    pub capacity: EssenceCapacity,
+    pub magnet: EssenceMagnet,
+}
+
+/// Points earned from essence overflow
+#[derive(Default, Deref, DerefMut)]
+pub struct Score(pub u32);
+
+/// What happens to essence collected while its pouch is full
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Overflow {
+    Heal,
+    Score,
+}
+
 #[derive(Component)]
 pub struct Essence;
 
+/// Time left before dropped essence starts blinking out
+#[derive(Component, Deref, DerefMut)]
+pub struct EssenceLifetime(Timer);
+
+/// Pickup that raises essence capacity for one element, or all of them if none is set
+#[derive(Component)]
+pub struct PouchUpgrade(Option<Element>);
+
+#[derive(Bundle)]
+pub struct PouchUpgradeBundle {
+    upgrade: PouchUpgrade,
+    collider: Collider,
+    sensor: Sensor,
+    groups: CollisionGroups,
+    events: ActiveEvents,
+    #[bundle]
+    sprite: SpriteBundle,
+}
+
+impl LdtkEntity for PouchUpgradeBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        _: &LayerInstance,
+        _: Option<&Handle<Image>>,
+        _: Option<&TilesetDefinition>,
+        asset_server: &AssetServer,
+        _: &mut Assets<TextureAtlas>,
+    ) -> Self {
+        let mut element = None;
+        for field in &entity_instance.field_instances {
+            if field.identifier.as_str() == "Element" {
+                element = match &field.value {
+                    FieldValue::Enum(value) => match value.as_deref() {
+                        Some("Fire") => Some(Element::Fire),
+                        Some("Water") => Some(Element::Water),
+                        Some("Wind") => Some(Element::Wind),
+                        Some("Lightning") => Some(Element::Lightning),
+                        Some("Earth") => Some(Element::Earth),
+                        None => None,
+                        _ => unreachable!(),
+                    },
+                    _ => unreachable!(),
+                }
+            }
+        }
+
+        PouchUpgradeBundle {
+            upgrade: PouchUpgrade(element),
+            collider: Collider::ball(4.0),
+            sensor: Sensor,
+            groups: CollisionGroups {
+                memberships: ESSENCE_COLLISION_GROUP,
+                filters: PLAYER_COLLISION_GROUP,
+            },
+            events: ActiveEvents::COLLISION_EVENTS,
+            sprite: SpriteBundle {
+                texture: asset_server.load("bottle.png"),
+                sprite: Sprite {
+                    color: element.map_or(Color::GOLD, |element| element.color()),
+                    ..default()
+                },
+                ..default()
+            },
+        }
+    }
+}
+
+/// Pickup that widens the essence magnet
+#[derive(Component)]
+pub struct MagnetUpgrade;
+
+#[derive(Bundle)]
+pub struct MagnetUpgradeBundle {
+    upgrade: MagnetUpgrade,
+    collider: Collider,
+    sensor: Sensor,
+    groups: CollisionGroups,
+    events: ActiveEvents,
+    #[bundle]
+    sprite: SpriteBundle,
+}
+
+impl LdtkEntity for MagnetUpgradeBundle {
+    fn bundle_entity(
+        _: &EntityInstance,
+        _: &LayerInstance,
+        _: Option<&Handle<Image>>,
+        _: Option<&TilesetDefinition>,
+        asset_server: &AssetServer,
+        _: &mut Assets<TextureAtlas>,
+    ) -> Self {
+        MagnetUpgradeBundle {
+            upgrade: MagnetUpgrade,
+            collider: Collider::ball(4.0),
+            sensor: Sensor,
+            groups: CollisionGroups {
+                memberships: ESSENCE_COLLISION_GROUP,
+                filters: PLAYER_COLLISION_GROUP,
+            },
+            events: ActiveEvents::COLLISION_EVENTS,
+            sprite: SpriteBundle {
+                texture: asset_server.load("bottle.png"),
+                sprite: Sprite {
+                    color: Color::SILVER,
+                    ..default()
+                },
+                ..default()
+            },
+        }
+    }
+}
+
+/// Spawns `count` essences scattered around `transform`
+pub fn spawn_essence(
+    cmd: &mut Commands,
+    element: Element,
+    transform: &GlobalTransform,
+    count: u32,
+) {
+    for _ in 0..count {
+        let mut transform = transform.compute_transform();
+        let offset = Vec2::new((fastrand::f32() - 0.5) * 8.0, (fastrand::f32() - 0.5) * 8.0);
+        transform.translation += offset.extend(0.0);
+        cmd.spawn_bundle(SpatialBundle {
+            transform,
+            ..default()
+        })
+        .insert_bundle((element, Essence, NotFromLevel));
+    }
+}
+
 pub struct Plugin;
 
 impl Plugin {
@@ -45,8 +233,16 @@ impl Plugin {
         mut cmd: Commands,
         q_essence: Query<(Entity, &Element), Added<Essence>>,
         element_icons: Res<ElementIconAtlases>,
+        mode: Res<GameMode>,
     ) {
         for (entity, element) in &q_essence {
+            if let Some(lifetime) = mode.essence_lifetime(*element) {
+                cmd.entity(entity)
+                    .insert(EssenceLifetime(Timer::from_seconds(
+                        (lifetime - ESSENCE_BLINK_TIME).max(0.0),
+                        false,
+                    )));
+            }
             cmd.entity(entity).insert_bundle((
                 UniformAnim(Timer::from_seconds(0.1, true)),
                 Sensor,
@@ -84,6 +280,9 @@ impl Plugin {
         q_essence: Query<(&Element, Entity), (With<Essence>, Without<Player>)>,
         q_player: Query<Entity, With<Player>>,
         mut counts: ResMut<EssenceCounts>,
+        capacity: Res<EssenceCapacity>,
+        mut score: ResMut<Score>,
+        mode: Res<GameMode>,
         mut event_writer: EventWriter<HealthChange>,
     ) {
         for event in event_reader.iter() {
@@ -110,25 +309,156 @@ impl Plugin {
                     }
                     let (element, essence) = essence_data;
 
-                    if counts[*element] < 5 {
-                        cmd.entity(essence).despawn_recursive();
+                    cmd.entity(essence).despawn_recursive();
+                    if counts[*element] < capacity[*element] {
                         *counts.get_mut(element).unwrap() += 1;
                         event_writer.send(HealthChange {
                             target: player,
-                            amount: 10.0,
+                            amount: ESSENCE_HEAL,
+                            element: None,
+                            source: None,
+                            ignore_iframes: false,
+                            hit: None,
                         });
+                        continue;
+                    }
+                    match mode.overflow() {
+                        Overflow::Heal => event_writer.send(HealthChange {
+                            target: player,
+                            amount: ESSENCE_OVERFLOW_HEAL,
+                            element: None,
+                            source: None,
+                            ignore_iframes: false,
+                            hit: None,
+                        }),
+                        Overflow::Score => **score += ESSENCE_OVERFLOW_SCORE,
                     }
                 }
                 _ => (),
             }
         }
     }
+
+    fn expire(
+        mut cmd: Commands,
+        mut q_essence: Query<(Entity, &mut EssenceLifetime, &mut UniformAnim)>,
+        time: Res<Time>,
+        time_scale: Res<TimeScale>,
+    ) {
+        for (entity, mut lifetime, mut anim) in &mut q_essence {
+            lifetime.tick(time.delta().mul_f32(**time_scale));
+            if lifetime.just_finished() {
+                anim.set_duration(Duration::from_secs_f32(ESSENCE_BLINK_FRAME_TIME));
+                cmd.entity(entity)
+                    .remove::<EssenceLifetime>()
+                    .insert(DespawnTimer(Timer::from_seconds(ESSENCE_BLINK_TIME, false)));
+            }
+        }
+    }
+
+    fn attract(
+        mut q_essence: Query<(&Element, &mut Transform), (With<Essence>, Without<Player>)>,
+        q_player: Query<&Transform, With<Player>>,
+        counts: Res<EssenceCounts>,
+        capacity: Res<EssenceCapacity>,
+        magnet: Res<EssenceMagnet>,
+        time: Res<Time>,
+        time_scale: Res<TimeScale>,
+    ) {
+        let player = match q_player.get_single() {
+            Ok(v) => v.translation.truncate(),
+            Err(_) => return,
+        };
+        let delta = time.delta_seconds() * **time_scale;
+
+        for (element, mut transform) in &mut q_essence {
+            if counts[*element] >= capacity[*element] {
+                continue;
+            }
+            let offset = player - transform.translation.truncate();
+            let distance = offset.length();
+            if distance > **magnet {
+                continue;
+            }
+            // Pulls harder the closer it gets, so it snaps into the player instead of trailing
+            let speed = ESSENCE_MAGNET_SPEED * (1.0 + (1.0 - distance / **magnet));
+            let step = offset.clamp_length_max(speed * delta);
+            transform.translation += step.extend(0.0);
+        }
+    }
+
+    fn upgrade_pouch(
+        mut cmd: Commands,
+        mut event_reader: EventReader<CollisionEvent>,
+        q_upgrade: Query<&PouchUpgrade>,
+        q_player: Query<(), With<Player>>,
+        mut capacity: ResMut<EssenceCapacity>,
+    ) {
+        for event in event_reader.iter() {
+            let (upgrade, entity) = match event {
+                CollisionEvent::Started(e1, e2, _) if q_player.contains(*e2) => {
+                    (q_upgrade.get(*e1), e1)
+                }
+                CollisionEvent::Started(e1, e2, _) if q_player.contains(*e1) => {
+                    (q_upgrade.get(*e2), e2)
+                }
+                _ => continue,
+            };
+            let upgrade = match upgrade {
+                Ok(v) => v,
+                Err(_) => continue,
+            };
+
+            for (element, amount) in capacity.iter_mut() {
+                if upgrade.0.map_or(true, |e| e == *element) {
+                    *amount = (*amount + POUCH_UPGRADE_AMOUNT).min(ESSENCE_MAX_CAPACITY);
+                }
+            }
+            cmd.entity(*entity).despawn_recursive();
+        }
+    }
+
+    fn upgrade_magnet(
+        mut cmd: Commands,
+        mut event_reader: EventReader<CollisionEvent>,
+        q_upgrade: Query<(), With<MagnetUpgrade>>,
+        q_player: Query<(), With<Player>>,
+        mut magnet: ResMut<EssenceMagnet>,
+    ) {
+        for event in event_reader.iter() {
+            let entity = match event {
+                CollisionEvent::Started(e1, e2, _) if q_player.contains(*e2) => e1,
+                CollisionEvent::Started(e1, e2, _) if q_player.contains(*e1) => e2,
+                _ => continue,
+            };
+            if !q_upgrade.contains(*entity) {
+                continue;
+            }
+
+            **magnet += MAGNET_UPGRADE_AMOUNT;
+            cmd.entity(*entity).despawn_recursive();
+        }
+    }
 }
 
 impl bevy::app::Plugin for Plugin {
     fn build(&self, app: &mut App) {
         app.add_system(Self::setup.run_in_state(GameState::InGame))
             .add_system(Self::collect.run_in_state(GameState::InGame))
-            .init_resource::<EssenceCounts>();
+            .add_system(
+                Self::attract
+                    .run_in_state(GameState::InGame)
+                    .run_not_in_state(PauseState::Paused),
+            )
+            .add_system(Self::expire.run_in_state(GameState::InGame))
+            .add_system(Self::upgrade_pouch.run_in_state(GameState::InGame))
+            .add_system(Self::upgrade_magnet.run_in_state(GameState::InGame))
+            .init_resource::<EssenceCounts>()
+            .init_resource::<EssenceCapacity>()
+            .init_resource::<EssenceMagnet>()
+            .init_resource::<Score>()
+            .init_resource::<LevelStartUpgrades>()
+            .register_ldtk_entity::<PouchUpgradeBundle>("PouchUpgrade")
+            .register_ldtk_entity::<MagnetUpgradeBundle>("MagnetUpgrade");
     }
 }
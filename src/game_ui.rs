@@ -2,12 +2,14 @@ use bevy::prelude::*;
 use iyes_loopless::prelude::*;
 
 use crate::{
-    health::{Dead, Health},
+    essence::{Overflow, Score},
+    health::{Dead, Health, Shield},
+    hotbar::PotionHotbar,
     level::{Reset, RestartLevel},
     player::Player,
     potion::PotionBrewState,
     utils::TimeScale,
-    GameState, PauseState,
+    GameMode, GameState, PauseState,
 };
 
 #[derive(Component)]
@@ -16,6 +18,15 @@ pub struct Root;
 #[derive(Component)]
 pub struct HealthBar;
 
+#[derive(Component)]
+pub struct ShieldBar;
+
+#[derive(Component)]
+pub struct HotbarSlot(usize);
+
+#[derive(Component)]
+pub struct ScoreText;
+
 #[derive(Component)]
 pub struct GameMenu;
 
@@ -34,7 +45,12 @@ pub struct MainMenuButton;
 pub struct Plugin;
 
 impl Plugin {
-    fn init(mut cmd: Commands, assets: Res<AssetServer>) {
+    fn init(
+        mut cmd: Commands,
+        assets: Res<AssetServer>,
+        hotbar: Res<PotionHotbar>,
+        mode: Res<GameMode>,
+    ) {
         cmd.spawn_bundle(NodeBundle {
             style: Style {
                 size: Size {
@@ -80,6 +96,87 @@ impl Plugin {
                     ..default()
                 })
                 .insert(HealthBar);
+                bar.spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size {
+                            width: Val::Percent(0.0),
+                            height: Val::Percent(100.0),
+                        },
+                        align_self: AlignSelf::FlexStart,
+                        ..default()
+                    },
+                    color: Color::CYAN.into(),
+                    ..default()
+                })
+                .insert(ShieldBar);
+            });
+
+            root.spawn_bundle(TextBundle {
+                text: Text::with_section(
+                    "",
+                    TextStyle {
+                        font: assets.load("fonts/DejaVuSansMono.ttf"),
+                        font_size: 32.0,
+                        color: Color::WHITE,
+                    },
+                    default(),
+                ),
+                style: Style {
+                    // Only modes that score overflow have anything to show
+                    display: if mode.overflow() == Overflow::Score {
+                        Display::Flex
+                    } else {
+                        Display::None
+                    },
+                    position: UiRect {
+                        right: Val::Px(20.0),
+                        top: Val::Px(10.0),
+                        ..default()
+                    },
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                ..default()
+            })
+            .insert(ScoreText);
+
+            root.spawn_bundle(NodeBundle {
+                style: Style {
+                    justify_content: JustifyContent::Center,
+                    size: Size {
+                        width: Val::Percent(100.0),
+                        height: Val::Px(40.0),
+                    },
+                    position: UiRect {
+                        left: Val::Px(0.0),
+                        bottom: Val::Px(40.0),
+                        ..default()
+                    },
+                    position_type: PositionType::Absolute,
+                    ..default()
+                },
+                color: Color::NONE.into(),
+                ..default()
+            })
+            .with_children(|strip| {
+                for i in 0..hotbar.len() {
+                    strip
+                        .spawn_bundle(NodeBundle {
+                            style: Style {
+                                justify_content: JustifyContent::SpaceEvenly,
+                                align_items: AlignItems::Center,
+                                size: Size {
+                                    width: Val::Px(80.0),
+                                    height: Val::Px(40.0),
+                                },
+                                margin: UiRect::all(Val::Px(4.0)),
+                                ..default()
+                            },
+                            color: Color::rgba(0.2, 0.2, 0.2, 0.75).into(),
+                            ..default()
+                        })
+                        .insert(HotbarSlot(i));
+                }
             });
 
             root.spawn_bundle(NodeBundle {
@@ -191,20 +288,82 @@ impl Plugin {
     }
 
     fn update_healthbar(
-        q_player: Query<&Health, With<Player>>,
-        mut q_bar: Query<&mut Style, With<HealthBar>>,
+        q_player: Query<(&Health, Option<&Shield>), With<Player>>,
+        mut q_bar: Query<&mut Style, (With<HealthBar>, Without<ShieldBar>)>,
+        mut q_shield_bar: Query<&mut Style, With<ShieldBar>>,
     ) {
-        let player_health_pct = match q_player.get_single() {
-            Ok(v) => v.percentage(),
+        let (health, shield) = match q_player.get_single() {
+            Ok(v) => v,
             Err(_) => return,
         };
         let mut bar = match q_bar.get_single_mut() {
             Ok(v) => v,
             Err(_) => return,
         };
+        let mut shield_bar = match q_shield_bar.get_single_mut() {
+            Ok(v) => v,
+            Err(_) => return,
+        };
+
+        // The bar stretches to fit the shield once health and shield add up past max health
+        let shield = shield.map_or(0.0, |shield| shield.amount());
+        let total = health.max().max(health.current() + shield);
+        bar.size.width = Val::Percent(health.percentage() * health.max() / total * 100.0);
+        shield_bar.size.width = Val::Percent(shield / total * 100.0);
+    }
+
+    fn update_score(mut q_text: Query<&mut Text, With<ScoreText>>, score: Res<Score>) {
+        let mut text = match q_text.get_single_mut() {
+            Ok(v) => v,
+            Err(_) => return,
+        };
+        text.sections[0].value = format!("Score: {}", **score);
+    }
 
-        bar.size.width = Val::Percent(player_health_pct * 100.0);
+    fn update_hotbar(
+        mut cmd: Commands,
+        q_slots: Query<(Entity, &HotbarSlot)>,
+        hotbar: Res<PotionHotbar>,
+    ) {
+        if !hotbar.is_changed() {
+            return;
+        }
+        for (entity, slot) in &q_slots {
+            cmd.entity(entity).despawn_descendants();
+            if let Some(potion) = &hotbar[slot.0] {
+                cmd.entity(entity).with_children(|slot| {
+                    for element in &potion.contents {
+                        slot.spawn_bundle(NodeBundle {
+                            style: Style {
+                                size: Size {
+                                    width: Val::Px(16.0),
+                                    height: Val::Px(16.0),
+                                },
+                                ..default()
+                            },
+                            color: element.color().into(),
+                            ..default()
+                        });
+                    }
+                    // One pip per potency tier
+                    for _ in 0..potion.tier {
+                        slot.spawn_bundle(NodeBundle {
+                            style: Style {
+                                size: Size {
+                                    width: Val::Px(6.0),
+                                    height: Val::Px(6.0),
+                                },
+                                ..default()
+                            },
+                            color: Color::WHITE.into(),
+                            ..default()
+                        });
+                    }
+                });
+            }
+        }
     }
+
     fn handle_restart_click(
         mut cmd: Commands,
         mut event_writer: EventWriter<RestartLevel>,
@@ -301,6 +460,8 @@ impl bevy::app::Plugin for Plugin {
             .add_enter_system(PauseState::Paused, Self::pause)
             .add_enter_system(PauseState::Unpaused, Self::unpause)
             .add_system(Self::update_healthbar.run_in_state(GameState::InGame))
+            .add_system(Self::update_hotbar.run_in_state(GameState::InGame))
+            .add_system(Self::update_score.run_in_state(GameState::InGame))
             .add_system(Self::handle_restart_click.run_in_state(GameState::InGame))
             .add_system(Self::handle_menu_click.run_in_state(GameState::InGame))
             .add_system(Self::handle_pause.run_in_state(GameState::InGame));
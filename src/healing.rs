@@ -0,0 +1,156 @@
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::*;
+use iyes_loopless::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    consts::*, health::HealthChange, level::NotFromLevel, player::Player, status::Regenerating,
+    GameState,
+};
+
+fn pickup_amount() -> f32 {
+    HEALTH_PICKUP_AMOUNT
+}
+
+/// Heals the player on touch, all at once or spread over `duration` seconds
+#[derive(Component, Deserialize, Clone)]
+pub struct HealthPickup {
+    #[serde(default = "pickup_amount")]
+    pub amount: f32,
+    #[serde(default)]
+    pub duration: Option<f32>,
+}
+
+impl Default for HealthPickup {
+    fn default() -> Self {
+        HealthPickup {
+            amount: HEALTH_PICKUP_AMOUNT,
+            duration: None,
+        }
+    }
+}
+
+impl HealthPickup {
+    fn bundle(self, texture: Handle<Image>) -> HealthPickupBundle {
+        HealthPickupBundle {
+            collider: Collider::ball(4.0),
+            sensor: Sensor,
+            groups: CollisionGroups {
+                memberships: ESSENCE_COLLISION_GROUP,
+                filters: PLAYER_COLLISION_GROUP,
+            },
+            events: ActiveEvents::COLLISION_EVENTS,
+            sprite: SpriteBundle {
+                texture,
+                sprite: Sprite {
+                    // Slow heals are a paler shade so they can be told apart
+                    color: if self.duration.is_some() {
+                        Color::PINK
+                    } else {
+                        Color::RED
+                    },
+                    ..default()
+                },
+                ..default()
+            },
+            pickup: self,
+        }
+    }
+}
+
+#[derive(Bundle)]
+pub struct HealthPickupBundle {
+    pickup: HealthPickup,
+    collider: Collider,
+    sensor: Sensor,
+    groups: CollisionGroups,
+    events: ActiveEvents,
+    #[bundle]
+    sprite: SpriteBundle,
+}
+
+impl LdtkEntity for HealthPickupBundle {
+    fn bundle_entity(
+        entity_instance: &EntityInstance,
+        _: &LayerInstance,
+        _: Option<&Handle<Image>>,
+        _: Option<&TilesetDefinition>,
+        asset_server: &AssetServer,
+        _: &mut Assets<TextureAtlas>,
+    ) -> Self {
+        let mut pickup = HealthPickup::default();
+        for field in &entity_instance.field_instances {
+            match (field.identifier.as_str(), &field.value) {
+                ("Amount", FieldValue::Float(Some(amount))) => pickup.amount = *amount,
+                ("Duration", FieldValue::Float(duration)) => pickup.duration = *duration,
+                _ => (),
+            }
+        }
+
+        pickup.bundle(asset_server.load("bottle.png"))
+    }
+}
+
+/// Drops a health pickup at `transform`, like an enemy dropping a potion
+pub fn spawn_health_pickup(
+    cmd: &mut Commands,
+    assets: &AssetServer,
+    pickup: HealthPickup,
+    transform: &GlobalTransform,
+) {
+    let mut bundle = pickup.bundle(assets.load("bottle.png"));
+    bundle.sprite.transform = transform.compute_transform();
+    cmd.spawn_bundle(bundle).insert(NotFromLevel);
+}
+
+pub struct Plugin;
+
+impl Plugin {
+    fn pick_up(
+        mut cmd: Commands,
+        mut event_reader: EventReader<CollisionEvent>,
+        q_pickup: Query<&HealthPickup>,
+        q_player: Query<(), With<Player>>,
+        mut event_writer: EventWriter<HealthChange>,
+    ) {
+        for event in event_reader.iter() {
+            let (pickup, entity, player) = match event {
+                CollisionEvent::Started(e1, e2, _) if q_player.contains(*e2) => {
+                    (q_pickup.get(*e1), e1, e2)
+                }
+                CollisionEvent::Started(e1, e2, _) if q_player.contains(*e1) => {
+                    (q_pickup.get(*e2), e2, e1)
+                }
+                _ => continue,
+            };
+            let pickup = match pickup {
+                Ok(v) => v,
+                Err(_) => continue,
+            };
+
+            match pickup.duration {
+                Some(duration) => {
+                    cmd.entity(*player)
+                        .insert(Regenerating::spread(pickup.amount, duration));
+                }
+                None => event_writer.send(HealthChange {
+                    target: *player,
+                    amount: pickup.amount,
+                    element: None,
+                    source: None,
+                    ignore_iframes: false,
+                    hit: None,
+                }),
+            }
+            cmd.entity(*entity).despawn_recursive();
+        }
+    }
+}
+
+impl bevy::app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(Self::pick_up.run_in_state(GameState::InGame))
+            .register_ldtk_entity::<HealthPickupBundle>("HealthPickup");
+    }
+}
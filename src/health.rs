@@ -1,12 +1,37 @@
-use bevy::prelude::*;
+use bevy::{
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
 use iyes_loopless::prelude::*;
 
-use crate::{utils::TimeScale, GameState};
+use crate::{consts::*, hitbox::HitEvent, status::Effect, utils::TimeScale, Element, GameState};
+
+/// Damage multipliers by element; elements not listed and physical damage hit in full
+#[derive(Default, Clone)]
+pub struct Resistances(HashMap<Element, f32>);
+
+impl Resistances {
+    pub fn new(multipliers: impl IntoIterator<Item = (Element, f32)>) -> Self {
+        Resistances(multipliers.into_iter().collect())
+    }
+
+    pub fn multiplier(&self, element: Option<Element>) -> f32 {
+        element
+            .and_then(|element| self.0.get(&element))
+            .copied()
+            .unwrap_or(1.0)
+    }
+}
 
 #[derive(Component)]
 pub struct Health {
     current: f32,
     max: f32,
+    resistances: Resistances,
+    /// Health regained per second once out of combat
+    regen: f32,
+    regen_delay: Timer,
+    regen_tick: Timer,
 }
 
 impl Health {
@@ -14,13 +39,74 @@ impl Health {
         Health {
             current: amount,
             max: amount,
+            resistances: Resistances::default(),
+            regen: 0.0,
+            regen_delay: Timer::from_seconds(HEALTH_REGEN_DELAY, false),
+            regen_tick: Timer::from_seconds(HEALTH_REGEN_TICK, true),
         }
     }
+    pub fn with_resistances(mut self, resistances: Resistances) -> Self {
+        self.resistances = resistances;
+        self
+    }
+    pub fn with_regen(mut self, regen: f32) -> Self {
+        self.regen = regen;
+        self
+    }
     pub fn percentage(&self) -> f32 {
         self.current / self.max
     }
+    pub fn current(&self) -> f32 {
+        self.current
+    }
+    pub fn max(&self) -> f32 {
+        self.max
+    }
 }
 
+/// Absorbs a flat amount of damage before health, refilling after a while without hits
+#[derive(Component)]
+pub struct Shield {
+    current: f32,
+    max: f32,
+    regen_delay: Timer,
+    /// Temporary shield on top, used up first and lost when its timer runs out
+    bonus: f32,
+    bonus_timer: Timer,
+}
+
+impl Shield {
+    pub fn new(amount: f32) -> Self {
+        Shield {
+            current: amount,
+            max: amount,
+            regen_delay: Timer::from_seconds(SHIELD_REGEN_DELAY, false),
+            bonus: 0.0,
+            bonus_timer: Timer::from_seconds(0.0, false),
+        }
+    }
+    pub fn grant(&mut self, amount: f32, duration: f32) {
+        self.bonus = self.bonus.max(amount);
+        self.bonus_timer = Timer::from_seconds(duration, false);
+    }
+    pub fn amount(&self) -> f32 {
+        self.current + self.bonus
+    }
+    /// Soaks up as much of `damage` as it can, returning what gets through
+    fn absorb(&mut self, damage: f32) -> f32 {
+        self.regen_delay.reset();
+        let from_bonus = damage.min(self.bonus);
+        self.bonus -= from_bonus;
+        let from_current = (damage - from_bonus).min(self.current);
+        self.current -= from_current;
+        damage - from_bonus - from_current
+    }
+}
+
+/// Fraction taken off every hit that gets past the shield
+#[derive(Component, Deref)]
+pub struct Armor(pub f32);
+
 #[derive(Component)]
 pub struct Dead;
 
@@ -35,9 +121,66 @@ impl DamageFlash {
     }
 }
 
+/// Ignores damage for a while after each hit, blinking until it wears off
+#[derive(Component)]
+pub struct IFrames {
+    timer: Timer,
+}
+
+impl IFrames {
+    pub fn new(duration: f32) -> Self {
+        let mut timer = Timer::from_seconds(duration, false);
+        // Start out expired so the first hit lands
+        timer.tick(timer.duration());
+        IFrames { timer }
+    }
+    pub fn active(&self) -> bool {
+        !self.timer.finished()
+    }
+}
+
+/// What kind of thing dealt damage
+#[derive(Clone)]
+pub enum DamageKind {
+    Kick,
+    /// Name of the potion recipe
+    Potion(String),
+    StatusTick(Effect),
+    EnemyAttack,
+}
+
+/// Who or what dealt damage
+#[derive(Clone)]
+pub struct DamageSource {
+    pub entity: Option<Entity>,
+    pub kind: DamageKind,
+}
+
 pub struct HealthChange {
     pub target: Entity,
     pub amount: f32,
+    /// Element of the damage, or `None` for physical damage and healing
+    pub element: Option<Element>,
+    /// `None` for healing
+    pub source: Option<DamageSource>,
+    /// Damage that lands even while the target has i-frames
+    pub ignore_iframes: bool,
+    /// Hit that dealt the damage, sent on with what landed once it goes through
+    pub hit: Option<HitEvent>,
+}
+
+/// Sent when an entity runs out of health, with whatever dealt the final blow
+pub struct Died {
+    pub entity: Entity,
+    pub source: Option<DamageSource>,
+}
+
+/// Sent for every `HealthChange` that went through, with the amount left after
+/// resistances and armor, counting what the shield soaked up
+pub struct HealthChanged {
+    pub entity: Entity,
+    pub amount: f32,
+    pub source: Option<DamageSource>,
 }
 
 pub struct Plugin;
@@ -45,17 +188,77 @@ pub struct Plugin;
 impl Plugin {
     fn update_health(
         mut cmd: Commands,
-        mut q_health: Query<(Entity, &mut Health)>,
+        mut q_health: Query<(
+            Entity,
+            &mut Health,
+            Option<&mut IFrames>,
+            Option<&mut Shield>,
+            Option<&Armor>,
+        )>,
         mut event_reader: EventReader<HealthChange>,
+        mut event_writer: EventWriter<Died>,
+        mut changed_writer: EventWriter<HealthChanged>,
+        mut hit_writer: EventWriter<HitEvent>,
     ) {
+        // Health is only removed once commands run, so later hits this frame still find it
+        let mut died = HashSet::new();
         for event in event_reader.iter() {
-            if let Ok((entity, mut health)) = q_health.get_mut(event.target) {
-                health.current += event.amount;
+            if died.contains(&event.target) {
+                continue;
+            }
+            if let Ok((entity, mut health, iframes, shield, armor)) = q_health.get_mut(event.target)
+            {
+                if let Some(mut iframes) = iframes {
+                    if event.amount < 0.0 && !event.ignore_iframes {
+                        if iframes.active() {
+                            continue;
+                        }
+                        iframes.timer.reset();
+                    }
+                }
+                // Damage goes through resistances, then the shield, then armor
+                let mut absorbed = 0.0;
+                let amount = if event.amount < 0.0 {
+                    let mut damage = -event.amount * health.resistances.multiplier(event.element);
+                    if let Some(mut shield) = shield {
+                        let through = shield.absorb(damage);
+                        absorbed = damage - through;
+                        damage = through;
+                    }
+                    if let Some(armor) = armor {
+                        damage *= 1.0 - **armor;
+                    }
+                    -damage
+                } else {
+                    event.amount
+                };
+                let before = health.current;
+                health.current += amount;
+                if amount < 0.0 {
+                    health.regen_delay.reset();
+                }
 
                 if health.current > health.max {
                     health.current = health.max;
                 } else if health.current < 0.0 {
                     cmd.entity(entity).remove::<Health>().insert(Dead);
+                    died.insert(entity);
+                    event_writer.send(Died {
+                        entity,
+                        source: event.source.clone(),
+                    });
+                }
+                let changed = health.current - before - absorbed;
+                changed_writer.send(HealthChanged {
+                    entity,
+                    amount: changed,
+                    source: event.source.clone(),
+                });
+                if let Some(hit) = &event.hit {
+                    hit_writer.send(HitEvent {
+                        damage: -changed,
+                        ..hit.clone()
+                    });
                 }
 
                 if event.amount < 0.0 {
@@ -101,13 +304,82 @@ impl Plugin {
             }
         }
     }
+
+    /// Regeneration goes through `HealthChange` like any other healing
+    fn regenerate(
+        mut q_health: Query<(Entity, &mut Health)>,
+        mut event_writer: EventWriter<HealthChange>,
+        time: Res<Time>,
+        time_scale: Res<TimeScale>,
+    ) {
+        let delta = time.delta().mul_f32(**time_scale);
+        for (entity, mut health) in &mut q_health {
+            if health.regen <= 0.0 || health.current >= health.max {
+                continue;
+            }
+            health.regen_delay.tick(delta);
+            if !health.regen_delay.finished() {
+                continue;
+            }
+            health.regen_tick.tick(delta);
+            if health.regen_tick.just_finished() {
+                event_writer.send(HealthChange {
+                    target: entity,
+                    amount: health.regen * HEALTH_REGEN_TICK,
+                    element: None,
+                    source: None,
+                    ignore_iframes: false,
+                    hit: None,
+                });
+            }
+        }
+    }
+
+    fn update_shields(
+        mut q_shield: Query<&mut Shield>,
+        time: Res<Time>,
+        time_scale: Res<TimeScale>,
+    ) {
+        let delta = time.delta().mul_f32(**time_scale);
+        for mut shield in &mut q_shield {
+            shield.bonus_timer.tick(delta);
+            if shield.bonus_timer.finished() {
+                shield.bonus = 0.0;
+            }
+            shield.regen_delay.tick(delta);
+            if shield.regen_delay.finished() && shield.current < shield.max {
+                shield.current =
+                    (shield.current + SHIELD_REGEN_RATE * delta.as_secs_f32()).min(shield.max);
+            }
+        }
+    }
+
+    fn update_iframes(
+        mut q_iframes: Query<(&mut IFrames, &mut Visibility)>,
+        time: Res<Time>,
+        time_scale: Res<TimeScale>,
+    ) {
+        for (mut iframes, mut visibility) in &mut q_iframes {
+            if !iframes.active() {
+                continue;
+            }
+            iframes.timer.tick(time.delta().mul_f32(**time_scale));
+            let blinks = (iframes.timer.elapsed_secs() / IFRAME_BLINK_PERIOD) as u32;
+            visibility.is_visible = !iframes.active() || blinks % 2 == 0;
+        }
+    }
 }
 
 impl bevy::app::Plugin for Plugin {
     fn build(&self, app: &mut App) {
         app.add_event::<HealthChange>()
+            .add_event::<Died>()
+            .add_event::<HealthChanged>()
             .add_system(Self::update_health.run_in_state(GameState::InGame))
             .add_system(Self::init_damage_flash.run_in_state(GameState::InGame))
-            .add_system(Self::update_damage_flash.run_in_state(GameState::InGame));
+            .add_system(Self::update_damage_flash.run_in_state(GameState::InGame))
+            .add_system(Self::regenerate.run_in_state(GameState::InGame))
+            .add_system(Self::update_shields.run_in_state(GameState::InGame))
+            .add_system(Self::update_iframes.run_in_state(GameState::InGame));
     }
 }
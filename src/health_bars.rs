@@ -0,0 +1,233 @@
+use bevy::{prelude::*, sprite::Anchor, utils::HashSet};
+use iyes_loopless::prelude::*;
+
+use crate::{
+    consts::*,
+    enemy::Rank,
+    health::{Dead, Health, HealthChanged},
+    Enemy, GameState,
+};
+
+/// Small bar floating above an enemy, pointing at the part that shrinks
+#[derive(Component)]
+pub struct EnemyHealthBar {
+    bar: Entity,
+    fill: Entity,
+}
+
+/// Marks enemies whose bar is docked to the HUD instead
+#[derive(Component)]
+pub struct DockedHealthBar;
+
+/// Column the docked bars of elites and bosses stack in
+#[derive(Component)]
+pub struct DockedBars;
+
+/// Bar on the HUD tracking an elite or boss
+#[derive(Component)]
+pub struct DockedBar {
+    enemy: Entity,
+    fill: Entity,
+}
+
+pub struct Plugin;
+
+impl Plugin {
+    fn init(mut cmd: Commands) {
+        cmd.spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size {
+                    width: Val::Percent(100.0),
+                    height: Val::Auto,
+                },
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::Center,
+                position: UiRect {
+                    top: Val::Px(10.0),
+                    ..default()
+                },
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(DockedBars);
+    }
+
+    /// Bars only show up once an enemy has been hurt
+    fn spawn_bars(
+        mut cmd: Commands,
+        q_enemy: Query<
+            (Entity, &Rank, &TextureAtlasSprite),
+            (
+                With<Enemy>,
+                With<Health>,
+                Without<EnemyHealthBar>,
+                Without<DockedHealthBar>,
+            ),
+        >,
+        q_docked: Query<Entity, With<DockedBars>>,
+        mut event_reader: EventReader<HealthChanged>,
+    ) {
+        let mut spawned = HashSet::new();
+        for event in event_reader.iter() {
+            if event.amount >= 0.0 || !spawned.insert(event.entity) {
+                continue;
+            }
+            let (enemy, rank, sprite) = match q_enemy.get(event.entity) {
+                Ok(v) => v,
+                Err(_) => continue,
+            };
+
+            if *rank == Rank::Normal {
+                let height = (0.5 - sprite.anchor.as_vec().y) * ELEMENTAL_SPRITE_HEIGHT
+                    + ENEMY_HEALTH_BAR_GAP;
+                let mut fill = None;
+                let bar = cmd
+                    .spawn_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::BLACK,
+                            custom_size: Some(Vec2::new(
+                                ENEMY_HEALTH_BAR_WIDTH,
+                                ENEMY_HEALTH_BAR_HEIGHT,
+                            )),
+                            ..default()
+                        },
+                        transform: Transform::from_xyz(0.0, height, 1.0),
+                        ..default()
+                    })
+                    .with_children(|bar| {
+                        fill = Some(
+                            bar.spawn_bundle(SpriteBundle {
+                                sprite: Sprite {
+                                    color: Color::RED,
+                                    custom_size: Some(Vec2::new(
+                                        ENEMY_HEALTH_BAR_WIDTH,
+                                        ENEMY_HEALTH_BAR_HEIGHT,
+                                    )),
+                                    anchor: Anchor::CenterLeft,
+                                    ..default()
+                                },
+                                transform: Transform::from_xyz(
+                                    -ENEMY_HEALTH_BAR_WIDTH / 2.0,
+                                    0.0,
+                                    0.1,
+                                ),
+                                ..default()
+                            })
+                            .id(),
+                        );
+                    })
+                    .id();
+                cmd.entity(enemy).add_child(bar).insert(EnemyHealthBar {
+                    bar,
+                    fill: fill.unwrap(),
+                });
+                continue;
+            }
+
+            let docked = match q_docked.get_single() {
+                Ok(v) => v,
+                Err(_) => continue,
+            };
+            let (width, height) = match rank {
+                Rank::Boss => (BOSS_HEALTH_BAR_WIDTH, BOSS_HEALTH_BAR_HEIGHT),
+                _ => (ELITE_HEALTH_BAR_WIDTH, ELITE_HEALTH_BAR_HEIGHT),
+            };
+            cmd.entity(enemy).insert(DockedHealthBar);
+            cmd.entity(docked).with_children(|docked| {
+                let mut fill = None;
+                docked
+                    .spawn_bundle(NodeBundle {
+                        style: Style {
+                            size: Size {
+                                width: Val::Percent(width),
+                                height: Val::Px(height),
+                            },
+                            margin: UiRect::all(Val::Px(2.0)),
+                            ..default()
+                        },
+                        color: Color::BLACK.into(),
+                        ..default()
+                    })
+                    .with_children(|bar| {
+                        fill = Some(
+                            bar.spawn_bundle(NodeBundle {
+                                style: Style {
+                                    size: Size {
+                                        width: Val::Percent(100.0),
+                                        height: Val::Percent(100.0),
+                                    },
+                                    ..default()
+                                },
+                                color: Color::RED.into(),
+                                ..default()
+                            })
+                            .id(),
+                        );
+                    })
+                    .insert(DockedBar {
+                        enemy,
+                        fill: fill.unwrap(),
+                    });
+            });
+        }
+    }
+
+    fn update_bars(q_enemy: Query<(&Health, &EnemyHealthBar)>, mut q_fill: Query<&mut Sprite>) {
+        for (health, bar) in &q_enemy {
+            if let Ok(mut sprite) = q_fill.get_mut(bar.fill) {
+                sprite.custom_size = Some(Vec2::new(
+                    health.percentage().max(0.0) * ENEMY_HEALTH_BAR_WIDTH,
+                    ENEMY_HEALTH_BAR_HEIGHT,
+                ));
+            }
+        }
+    }
+
+    fn remove_bars(mut cmd: Commands, q_enemy: Query<(Entity, &EnemyHealthBar), Added<Dead>>) {
+        for (enemy, bar) in &q_enemy {
+            cmd.entity(bar.bar).despawn_recursive();
+            cmd.entity(enemy).remove::<EnemyHealthBar>();
+        }
+    }
+
+    /// Docked bars also go away when their enemy is unloaded along with its level
+    fn update_docked_bars(
+        mut cmd: Commands,
+        q_bars: Query<(Entity, &DockedBar)>,
+        q_enemy: Query<&Health, Without<Dead>>,
+        mut q_fill: Query<&mut Style>,
+    ) {
+        for (entity, bar) in &q_bars {
+            let health = match q_enemy.get(bar.enemy) {
+                Ok(v) => v,
+                Err(_) => {
+                    cmd.entity(entity).despawn_recursive();
+                    continue;
+                }
+            };
+            if let Ok(mut style) = q_fill.get_mut(bar.fill) {
+                style.size.width = Val::Percent(health.percentage().max(0.0) * 100.0);
+            }
+        }
+    }
+
+    fn cleanup(mut cmd: Commands, q_docked: Query<Entity, With<DockedBars>>) {
+        for entity in &q_docked {
+            cmd.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+impl bevy::app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_enter_system(GameState::InGame, Self::init)
+            .add_exit_system(GameState::InGame, Self::cleanup)
+            .add_system(Self::spawn_bars.run_in_state(GameState::InGame))
+            .add_system(Self::update_bars.run_in_state(GameState::InGame))
+            .add_system(Self::remove_bars.run_in_state(GameState::InGame))
+            .add_system(Self::update_docked_bars.run_in_state(GameState::InGame));
+    }
+}
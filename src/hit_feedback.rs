@@ -0,0 +1,112 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{
+    consts::*,
+    hitbox::{DamageElement, HitEvent},
+    level::NotFromLevel,
+    player::Player,
+    utils::DespawnTimer,
+    GameState, MainCamera,
+};
+
+/// How hard the camera is shaking, from 0 to `SCREEN_SHAKE_MAX`, fading over time
+#[derive(Default, Deref, DerefMut)]
+pub struct ScreenShake(pub f32);
+
+pub struct Plugin;
+
+impl Plugin {
+    /// Only hits on the player shake the screen, harder ones shake it more
+    fn shake_on_hit(
+        mut event_reader: EventReader<HitEvent>,
+        q_player: Query<(), With<Player>>,
+        mut shake: ResMut<ScreenShake>,
+    ) {
+        for event in event_reader.iter() {
+            if !q_player.contains(event.target) {
+                continue;
+            }
+            **shake = (**shake
+                + event.damage * SCREEN_SHAKE_PER_DAMAGE
+                + event.knockback.length() * SCREEN_SHAKE_PER_KNOCKBACK)
+                .min(SCREEN_SHAKE_MAX);
+        }
+    }
+
+    /// Nudges the camera off its focus; `follow_camera_focus` pulls it back
+    fn shake_camera(
+        mut q_camera: Query<&mut Transform, With<MainCamera>>,
+        mut shake: ResMut<ScreenShake>,
+        time: Res<Time>,
+    ) {
+        if **shake <= 0.0 {
+            return;
+        }
+        let mut camera_pos = match q_camera.get_single_mut() {
+            Ok(v) => v,
+            Err(_) => return,
+        };
+
+        // Squared so small hits barely move the camera
+        let offset = Vec2::new(fastrand::f32() - 0.5, fastrand::f32() - 0.5)
+            * 2.0
+            * shake.powi(2)
+            * SCREEN_SHAKE_OFFSET;
+        camera_pos.translation += offset.extend(0.0);
+        **shake = (**shake - SCREEN_SHAKE_DECAY * time.delta_seconds()).max(0.0);
+    }
+
+    /// Flash where a hit landed, in the colour of its element
+    fn spawn_sparks(
+        mut cmd: Commands,
+        mut event_reader: EventReader<HitEvent>,
+        q_element: Query<&DamageElement>,
+    ) {
+        for event in event_reader.iter() {
+            if event.damage <= 0.0 && event.status.is_none() {
+                continue;
+            }
+            let color = match q_element.get(event.hitbox) {
+                Ok(element) => element.0.color(),
+                Err(_) => Color::WHITE,
+            };
+            let mut size =
+                (HIT_SPARK_SIZE + event.damage * HIT_SPARK_DAMAGE_SCALE).min(HIT_SPARK_MAX_SIZE);
+            if event.status.is_some() {
+                size *= HIT_SPARK_STATUS_SCALE;
+            }
+
+            cmd.spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::splat(size)),
+                    ..default()
+                },
+                transform: Transform::from_translation(event.position.extend(HIT_SPARK_Z)),
+                ..default()
+            })
+            .insert_bundle((
+                DespawnTimer(Timer::from_seconds(HIT_SPARK_LIFETIME, false)),
+                NotFromLevel,
+            ));
+        }
+    }
+
+    fn cleanup(mut shake: ResMut<ScreenShake>) {
+        **shake = 0.0;
+    }
+}
+
+impl bevy::app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScreenShake>()
+            .add_exit_system(GameState::InGame, Self::cleanup)
+            .add_system(Self::shake_on_hit.run_in_state(GameState::InGame))
+            .add_system(Self::spawn_sparks.run_in_state(GameState::InGame))
+            .add_system_to_stage(
+                CoreStage::Last,
+                Self::shake_camera.run_in_state(GameState::InGame),
+            );
+    }
+}
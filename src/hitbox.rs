@@ -1,17 +1,99 @@
 use bevy::utils::Duration;
 
-use bevy::{prelude::*, utils::HashSet};
+use bevy::{
+    ecs::query::WorldQuery,
+    prelude::*,
+    utils::{HashMap, HashSet},
+};
 use bevy_rapier2d::prelude::*;
 use iyes_loopless::prelude::*;
+use serde::Deserialize;
 
 use crate::{
-    health::HealthChange, hitstun::HitstunTimer, player::Player, status::Effect, utils::TimeScale,
-    Enemy, GameState,
+    health::{DamageSource, HealthChange},
+    hitstun::HitstunTimer,
+    status::ApplyEffect,
+    utils::TimeScale,
+    Element, GameState,
 };
 
 #[derive(Component)]
 pub struct Hitbox;
 
+/// Side something fights on; hitboxes and the targets they land on both have one
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+pub enum Faction {
+    Player,
+    Enemy,
+}
+
+/// How hitboxes of one faction treat targets of another
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Relation {
+    Hostile,
+    Friendly,
+    /// Only hurts while friendly fire is turned on
+    FriendlyFire,
+}
+
+/// Which factions' hitboxes hurt which targets. Pairs not listed are friendly within
+/// a faction and hostile across factions.
+pub struct FactionRelations {
+    relations: HashMap<(Faction, Faction), Relation>,
+    pub friendly_fire: bool,
+}
+
+impl Default for FactionRelations {
+    fn default() -> Self {
+        let mut relations = FactionRelations {
+            relations: HashMap::new(),
+            friendly_fire: false,
+        };
+        relations.set(Faction::Player, Faction::Player, Relation::FriendlyFire);
+        relations
+    }
+}
+
+impl FactionRelations {
+    pub fn set(&mut self, attacker: Faction, target: Faction, relation: Relation) {
+        self.relations.insert((attacker, target), relation);
+    }
+
+    pub fn relation(&self, attacker: Faction, target: Faction) -> Relation {
+        match self.relations.get(&(attacker, target)) {
+            Some(relation) => *relation,
+            None if attacker == target => Relation::Friendly,
+            None => Relation::Hostile,
+        }
+    }
+
+    /// Whether a hitbox of `attacker` hurts a target of `target`; hitboxes without a
+    /// faction hurt everyone
+    pub fn hurts(&self, attacker: Option<Faction>, target: Faction) -> bool {
+        let attacker = match attacker {
+            Some(v) => v,
+            None => return true,
+        };
+        match self.relation(attacker, target) {
+            Relation::Hostile => true,
+            Relation::Friendly => false,
+            Relation::FriendlyFire => self.friendly_fire,
+        }
+    }
+}
+
+/// Element of the damage a hitbox deals; hitboxes without one deal physical damage
+#[derive(Component, Clone, Copy)]
+pub struct DamageElement(pub Element);
+
+/// Who or what the damage dealt by a hitbox is credited to
+#[derive(Component, Clone)]
+pub struct HitSource(pub DamageSource);
+
+/// Damage from this hitbox lands even while the target has i-frames
+#[derive(Component)]
+pub struct IgnoreIFrames;
+
 #[derive(Component)]
 pub struct Single(Option<()>);
 
@@ -21,13 +103,19 @@ impl Single {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Falloff {
     ratio: f32,
     start: f32,
     end: f32,
 }
 
+impl Default for Falloff {
+    fn default() -> Self {
+        Falloff::none()
+    }
+}
+
 impl Falloff {
     pub fn none() -> Self {
         Falloff {
@@ -137,51 +225,76 @@ impl DamagePeriodic {
 }
 
 #[derive(Component, Deref)]
-pub struct StatusEffect(pub Effect);
+pub struct StatusEffect(pub ApplyEffect);
+
+/// Sent for every hitbox landing on a target, and for every tick of periodic damage,
+/// so feedback like screen shake, sound or particles doesn't have to re-derive hits.
+/// Hits that deal damage are only sent once `update_health` lets the damage through.
+#[derive(Clone)]
+pub struct HitEvent {
+    pub hitbox: Entity,
+    pub target: Entity,
+    /// Sensors have no contact points, so this is where the target was when hit
+    pub position: Vec2,
+    /// Damage that landed after resistances and armor, counting what the shield
+    /// soaked up, zero if the hit did none
+    pub damage: f32,
+    pub knockback: Vec2,
+    pub status: Option<ApplyEffect>,
+}
+
+/// Everything a hitbox can do to the targets it lands on
+#[derive(WorldQuery)]
+#[world_query(mutable)]
+struct HitboxQuery<'w> {
+    entity: Entity,
+    transform: &'w GlobalTransform,
+    hitstun: Option<&'w Hitstun>,
+    radial_impulse: Option<&'w RadialImpulse>,
+    directed_impulse: Option<&'w DirectedImpulse>,
+    radial_force: Option<&'w mut RadialForce>,
+    directed_force: Option<&'w mut DirectedForce>,
+    damage_once: Option<&'w mut DamageOnce>,
+    damage_periodic: Option<&'w mut DamagePeriodic>,
+    single: Option<&'w mut Single>,
+    status_effect: Option<&'w StatusEffect>,
+    damage_element: Option<&'w DamageElement>,
+    hit_source: Option<&'w HitSource>,
+    ignore_iframes: Option<&'w IgnoreIFrames>,
+    faction: Option<&'w Faction>,
+}
 
 pub struct Plugin;
 
 impl Plugin {
-    fn handle_hits<T: Component>(
+    fn handle_hits(
         mut cmd: Commands,
         mut event_reader: EventReader<CollisionEvent>,
-        mut q_target: Query<(&GlobalTransform, &mut HitstunTimer), (With<T>, Without<Hitbox>)>,
-        mut q_hitbox: Query<
-            (
-                &GlobalTransform,
-                Option<&Hitstun>,
-                Option<&RadialImpulse>,
-                Option<&DirectedImpulse>,
-                Option<&mut RadialForce>,
-                Option<&mut DirectedForce>,
-                Option<&mut DamageOnce>,
-                Option<&mut DamagePeriodic>,
-                Option<&mut Single>,
-                Option<&StatusEffect>,
-            ),
-            (Without<T>, With<Hitbox>),
-        >,
+        mut q_target: Query<(&GlobalTransform, &mut HitstunTimer, &Faction), Without<Hitbox>>,
+        mut q_hitbox: Query<HitboxQuery, With<Hitbox>>,
         mut event_writer: EventWriter<HealthChange>,
+        mut hit_writer: EventWriter<HitEvent>,
+        relations: Res<FactionRelations>,
     ) {
         for event in event_reader.iter() {
             match event {
                 CollisionEvent::Started(e1, e2, _) => {
-                    let hitbox_data;
+                    let hitbox;
                     let target_data;
                     let target_entity;
                     if let Ok(enemy) = q_target.get_mut(*e1) {
-                        if let Ok(hitbox) = q_hitbox.get_mut(*e2) {
+                        if let Ok(h) = q_hitbox.get_mut(*e2) {
                             target_entity = e1;
                             target_data = enemy;
-                            hitbox_data = hitbox;
+                            hitbox = h;
                         } else {
                             continue;
                         }
                     } else if let Ok(enemy) = q_target.get_mut(*e2) {
-                        if let Ok(hitbox) = q_hitbox.get_mut(*e1) {
+                        if let Ok(h) = q_hitbox.get_mut(*e1) {
                             target_entity = e2;
                             target_data = enemy;
-                            hitbox_data = hitbox;
+                            hitbox = h;
                         } else {
                             continue;
                         }
@@ -189,20 +302,11 @@ impl Plugin {
                         continue;
                     }
 
-                    let (enemy_transform, mut hitstun_timer) = target_data;
-                    let (
-                        hitbox_transform,
-                        hitstun,
-                        radial_impulse,
-                        directed_impulse,
-                        radial_force,
-                        directed_force,
-                        damage_once,
-                        damage_periodic,
-                        single,
-                        status_effect,
-                    ) = hitbox_data;
-                    if let Some(mut single) = single {
+                    let (enemy_transform, mut hitstun_timer, target_faction) = target_data;
+                    if !relations.hurts(hitbox.faction.copied(), *target_faction) {
+                        continue;
+                    }
+                    if let Some(mut single) = hitbox.single {
                         if single.0.is_none() {
                             single.0 = Some(());
                         } else {
@@ -210,89 +314,109 @@ impl Plugin {
                         }
                     }
 
-                    if let Some(hitstun) = hitstun {
+                    if let Some(hitstun) = hitbox.hitstun {
                         hitstun_timer.set_duration(Duration::from_secs_f32(**hitstun));
                         hitstun_timer.reset();
                     }
-                    if let Some(radial_impulse) = radial_impulse {
+                    let mut knockback = None;
+                    if let Some(radial_impulse) = hitbox.radial_impulse {
                         let force_direction = (enemy_transform.translation()
-                            - hitbox_transform.translation())
+                            - hitbox.transform.translation())
                         .truncate();
 
-                        cmd.entity(*target_entity).insert(ExternalImpulse {
-                            impulse: force_direction.normalize()
+                        knockback = Some(
+                            force_direction.normalize()
                                 * radial_impulse.force
                                 * radial_impulse.falloff.amount(force_direction.length()),
-                            torque_impulse: 0.0,
-                        });
+                        );
                     }
 
-                    if let Some(directed_impulse) = directed_impulse {
+                    // A directed impulse replaces the radial one
+                    if let Some(directed_impulse) = hitbox.directed_impulse {
+                        knockback = Some(**directed_impulse);
+                    }
+
+                    if let Some(impulse) = knockback {
                         cmd.entity(*target_entity).insert(ExternalImpulse {
-                            impulse: **directed_impulse,
+                            impulse,
                             torque_impulse: 0.0,
                         });
                     }
 
-                    if let Some(mut radial_force) = radial_force {
+                    if let Some(mut radial_force) = hitbox.radial_force {
                         radial_force.hostages.insert(*target_entity);
                     }
 
-                    if let Some(mut directed_force) = directed_force {
+                    if let Some(mut directed_force) = hitbox.directed_force {
                         directed_force.hostages.insert(*target_entity);
                     }
 
-                    if let Some(mut damage_once) = damage_once {
+                    if let Some(mut damage_periodic) = hitbox.damage_periodic {
+                        damage_periodic.hostages.insert(*target_entity);
+                    }
+
+                    let status = hitbox.status_effect.map(|effect| effect.0.clone());
+                    if let Some(status) = &status {
+                        cmd.entity(*target_entity).insert(status.clone());
+                    }
+
+                    let mut hit = Some(HitEvent {
+                        hitbox: hitbox.entity,
+                        target: *target_entity,
+                        position: enemy_transform.translation().truncate(),
+                        damage: 0.0,
+                        knockback: knockback.unwrap_or(Vec2::ZERO),
+                        status,
+                    });
+                    if let Some(mut damage_once) = hitbox.damage_once {
                         if damage_once.hit.insert(*target_entity) {
                             let distance = (enemy_transform.translation()
-                                - hitbox_transform.translation())
+                                - hitbox.transform.translation())
                             .truncate()
                             .length();
                             event_writer.send(HealthChange {
                                 target: *target_entity,
                                 amount: -damage_once.amount * damage_once.falloff.amount(distance),
-                            })
+                                element: hitbox.damage_element.map(|element| element.0),
+                                source: hitbox.hit_source.map(|source| source.0.clone()),
+                                ignore_iframes: hitbox.ignore_iframes.is_some(),
+                                hit: hit.take(),
+                            });
                         }
                     }
-
-                    if let Some(mut damage_periodic) = damage_periodic {
-                        damage_periodic.hostages.insert(*target_entity);
-                    }
-
-                    if let Some(status_effect) = status_effect {
-                        cmd.entity(*target_entity).insert(status_effect.0.clone());
+                    // Without damage there's nothing for i-frames to block
+                    if let Some(hit) = hit {
+                        hit_writer.send(hit);
                     }
                 }
                 CollisionEvent::Stopped(e1, e2, _) => {
                     let enemy_entity;
-                    let hitbox_data;
+                    let hitbox;
                     if let Ok(_) = q_target.get(*e1) {
                         if let Ok(h) = q_hitbox.get_mut(*e2) {
                             enemy_entity = e1;
-                            hitbox_data = h;
+                            hitbox = h;
                         } else {
                             continue;
                         }
                     } else if let Ok(_) = q_target.get(*e2) {
                         if let Ok(h) = q_hitbox.get_mut(*e1) {
                             enemy_entity = e2;
-                            hitbox_data = h;
+                            hitbox = h;
                         } else {
                             continue;
                         }
                     } else {
                         continue;
                     }
-                    let (_, _, _, _, radial_force, directed_force, _, damage_periodic, _, _) =
-                        hitbox_data;
 
-                    if let Some(mut radial_force) = radial_force {
+                    if let Some(mut radial_force) = hitbox.radial_force {
                         radial_force.hostages.remove(enemy_entity);
                     }
-                    if let Some(mut directed_force) = directed_force {
+                    if let Some(mut directed_force) = hitbox.directed_force {
                         directed_force.hostages.remove(enemy_entity);
                     }
-                    if let Some(mut damage_periodic) = damage_periodic {
+                    if let Some(mut damage_periodic) = hitbox.damage_periodic {
                         damage_periodic.hostages.remove(enemy_entity);
                     }
                 }
@@ -300,27 +424,19 @@ impl Plugin {
         }
     }
 
-    fn update_continuous_boxes<T: Component>(
+    /// Only targets `handle_hits` let through end up held by these hitboxes, so
+    /// factions don't need checking again
+    fn update_continuous_boxes(
         mut cmd: Commands,
-        mut q_enemy: Query<
-            (Entity, &GlobalTransform, &mut HitstunTimer),
-            (With<T>, Without<Hitbox>),
-        >,
-        mut q_hitbox: Query<
-            (
-                &GlobalTransform,
-                Option<&RadialForce>,
-                Option<&DirectedForce>,
-                Option<&mut DamagePeriodic>,
-            ),
-            (Without<T>, With<Hitbox>),
-        >,
+        mut q_enemy: Query<(Entity, &GlobalTransform, &mut HitstunTimer), Without<Hitbox>>,
+        mut q_hitbox: Query<HitboxQuery, With<Hitbox>>,
         mut event_writer: EventWriter<HealthChange>,
         time: Res<Time>,
         time_scale: Res<TimeScale>,
     ) {
-        for (origin, radial_force, directed_force, damage_periodic) in &mut q_hitbox {
-            if let Some(radial_force) = radial_force {
+        for hitbox in &mut q_hitbox {
+            let origin = hitbox.transform;
+            if let Some(radial_force) = hitbox.radial_force {
                 let mut iter = q_enemy.iter_many_mut(radial_force.hostages.iter());
 
                 while let Some((entity, transform, _)) = iter.fetch_next() {
@@ -336,7 +452,7 @@ impl Plugin {
                 }
             }
 
-            if let Some(directed_force) = directed_force {
+            if let Some(directed_force) = hitbox.directed_force {
                 let mut iter = q_enemy.iter_many_mut(directed_force.hostages.iter());
 
                 while let Some((entity, _, _)) = iter.fetch_next() {
@@ -347,7 +463,7 @@ impl Plugin {
                 }
             }
 
-            if let Some(mut damage_periodic) = damage_periodic {
+            if let Some(mut damage_periodic) = hitbox.damage_periodic {
                 damage_periodic
                     .period
                     .tick(time.delta().mul_f32(**time_scale));
@@ -358,10 +474,22 @@ impl Plugin {
                         let distance = (transform.translation() - origin.translation())
                             .truncate()
                             .length();
+                        let damage =
+                            damage_periodic.amount * damage_periodic.falloff.amount(distance);
                         event_writer.send(HealthChange {
                             target: entity,
-                            amount: -damage_periodic.amount
-                                * damage_periodic.falloff.amount(distance),
+                            amount: -damage,
+                            element: hitbox.damage_element.map(|element| element.0),
+                            source: hitbox.hit_source.map(|source| source.0.clone()),
+                            ignore_iframes: hitbox.ignore_iframes.is_some(),
+                            hit: Some(HitEvent {
+                                hitbox: hitbox.entity,
+                                target: entity,
+                                position: transform.translation().truncate(),
+                                damage: 0.0,
+                                knockback: Vec2::ZERO,
+                                status: None,
+                            }),
                         });
                     }
                 }
@@ -372,25 +500,17 @@ impl Plugin {
 
 impl bevy::app::Plugin for Plugin {
     fn build(&self, app: &mut App) {
-        app.add_system(
-            Self::handle_hits::<Enemy>
-                .run_in_state(GameState::InGame)
-                .label("handle_hits"),
-        )
-        .add_system(
-            Self::update_continuous_boxes::<Enemy>
-                .run_in_state(GameState::InGame)
-                .before("handle_hits"),
-        )
-        .add_system(
-            Self::handle_hits::<Player>
-                .run_in_state(GameState::InGame)
-                .label("handle_hits"),
-        )
-        .add_system(
-            Self::update_continuous_boxes::<Player>
-                .run_in_state(GameState::InGame)
-                .before("handle_hits"),
-        );
+        app.init_resource::<FactionRelations>()
+            .add_event::<HitEvent>()
+            .add_system(
+                Self::handle_hits
+                    .run_in_state(GameState::InGame)
+                    .label("handle_hits"),
+            )
+            .add_system(
+                Self::update_continuous_boxes
+                    .run_in_state(GameState::InGame)
+                    .before("handle_hits"),
+            );
     }
 }
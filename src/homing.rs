@@ -3,7 +3,10 @@ use bevy::prelude::*;
 use bevy_rapier2d::prelude::*;
 use iyes_loopless::prelude::*;
 
-use crate::{Enemy, GameState};
+use crate::{
+    hitbox::{Faction, FactionRelations, Relation},
+    GameState,
+};
 
 #[derive(Component)]
 pub struct Homing {
@@ -13,20 +16,19 @@ pub struct Homing {
 pub struct Plugin;
 
 impl Plugin {
-    fn calc_homing<T: Component>(
-        mut q_homing: Query<(Entity, &GlobalTransform, &mut Velocity, &Homing), Without<T>>,
-        q_targets: Query<&GlobalTransform, (Without<Homing>, With<T>)>,
+    /// Homes in on whatever its faction is hostile to, friendly fire or not
+    fn calc_homing(
+        mut q_homing: Query<(Entity, &GlobalTransform, &mut Velocity, &Homing, &Faction)>,
+        q_targets: Query<(&GlobalTransform, &Faction), Without<Homing>>,
         rapier_ctx: Res<RapierContext>,
+        relations: Res<FactionRelations>,
     ) {
-        for (entity, transform, mut velocity, homing) in &mut q_homing {
+        for (entity, transform, mut velocity, homing, faction) in &mut q_homing {
             let mut targets = vec![];
             for (e1, e2, _) in rapier_ctx.intersections_with(entity) {
-                if e1 == entity {
-                    if let Ok(transform) = q_targets.get(e2) {
-                        targets.push(transform.translation());
-                    }
-                } else if e2 == entity {
-                    if let Ok(transform) = q_targets.get(e1) {
+                let other = if e1 == entity { e2 } else { e1 };
+                if let Ok((transform, target_faction)) = q_targets.get(other) {
+                    if relations.relation(*faction, *target_faction) == Relation::Hostile {
                         targets.push(transform.translation());
                     }
                 }
@@ -45,6 +47,6 @@ impl Plugin {
 
 impl bevy::app::Plugin for Plugin {
     fn build(&self, app: &mut App) {
-        app.add_system(Self::calc_homing::<Enemy>.run_in_state(GameState::InGame));
+        app.add_system(Self::calc_homing.run_in_state(GameState::InGame));
     }
 }
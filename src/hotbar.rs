@@ -0,0 +1,92 @@
+use bevy::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{
+    consts::*,
+    player::Player,
+    potion::{PotionBrewData, PotionBrewState, PotionType, ThrowPotion},
+    utils::MousePosition,
+    GameState, PauseState,
+};
+
+const HOTBAR_KEYS: [KeyCode; 5] = [
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+];
+
+/// Potions brewed ahead of time, ready to be thrown without the brew wheel
+#[derive(Deref, DerefMut)]
+pub struct PotionHotbar(Vec<Option<PotionType>>);
+
+impl Default for PotionHotbar {
+    fn default() -> Self {
+        PotionHotbar(vec![None; HOTBAR_SLOTS])
+    }
+}
+
+impl PotionHotbar {
+    /// Puts a brewed potion in the first empty slot, returning false if there is none
+    pub fn store(&mut self, potion: PotionType) -> bool {
+        match self.iter_mut().find(|slot| slot.is_none()) {
+            Some(slot) => {
+                *slot = Some(potion);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+pub struct Plugin;
+
+impl Plugin {
+    fn throw(
+        keys: Res<Input<KeyCode>>,
+        mouse_pos: Res<MousePosition>,
+        q_player: Query<&Transform, With<Player>>,
+        brew_state: Res<PotionBrewState>,
+        brew_data: Res<PotionBrewData>,
+        mut hotbar: ResMut<PotionHotbar>,
+        mut event_writer: EventWriter<ThrowPotion>,
+    ) {
+        if *brew_state != PotionBrewState::Inactive {
+            return;
+        }
+        let player = match q_player.get_single() {
+            Ok(v) => v,
+            Err(_) => return,
+        };
+
+        for (slot, key) in hotbar.iter_mut().zip(HOTBAR_KEYS) {
+            if !keys.just_pressed(key) {
+                continue;
+            }
+            if let Some(potion) = slot.take() {
+                let pos = player.translation.truncate();
+                event_writer.send(ThrowPotion {
+                    potion,
+                    direction: (mouse_pos.truncate() - pos).normalize_or_zero(),
+                    target: mouse_pos.truncate(),
+                    bounces: brew_data.bounces,
+                    mode: brew_data.mode,
+                });
+            }
+        }
+    }
+}
+
+impl bevy::app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(
+            Self::throw
+                .run_in_state(GameState::InGame)
+                .run_not_in_state(PauseState::Paused)
+                // The same keys pick brew wheel sectors, so don't throw on the press that closes it
+                .before("update_brew"),
+        )
+        .init_resource::<PotionHotbar>();
+    }
+}
@@ -6,7 +6,8 @@ use bevy_ecs_ldtk::utils::translation_to_grid_coords;
 use bevy_rapier2d::prelude::*;
 use iyes_loopless::prelude::*;
 
-use crate::essence::EssenceCounts;
+use crate::essence::{EssenceCapacity, EssenceCounts, EssenceMagnet, LevelStartUpgrades, Score};
+use crate::hotbar::PotionHotbar;
 use crate::player::Player;
 use crate::GameState;
 use crate::{consts::*, PauseState};
@@ -128,6 +129,43 @@ impl Default for GameEndBundle {
     }
 }
 
+#[derive(Bundle, LdtkEntity)]
+pub struct AltarEntity {
+    #[bundle]
+    #[sprite_sheet_bundle("minimal.png", 16.0, 16.0, 4, 5, 0.0, 0.0, 9)]
+    sprite_sheet: SpriteSheetBundle,
+    #[bundle]
+    altar: AltarBundle,
+}
+
+/// Where essence can be transmuted into other elements
+#[derive(Component, Default)]
+pub struct Altar;
+
+#[derive(Bundle)]
+struct AltarBundle {
+    collider: Collider,
+    sensor: Sensor,
+    altar: Altar,
+    groups: CollisionGroups,
+    events: ActiveEvents,
+}
+
+impl Default for AltarBundle {
+    fn default() -> Self {
+        AltarBundle {
+            collider: Collider::cuboid(8.0, 8.0),
+            sensor: Sensor,
+            altar: Altar,
+            groups: CollisionGroups {
+                memberships: WALL_COLLISION_GROUP,
+                filters: PLAYER_COLLISION_GROUP,
+            },
+            events: ActiveEvents::COLLISION_EVENTS,
+        }
+    }
+}
+
 #[derive(Default, Deref, DerefMut)]
 pub struct WalkableTiles(HashSet<IVec2>);
 
@@ -230,9 +268,15 @@ impl Plugin {
         mut event_reader: EventReader<ChangeLevel>,
         mut current_level: ResMut<CurrentLevel>,
         q_despawn: Query<Entity, With<NotFromLevel>>,
+        capacity: Res<EssenceCapacity>,
+        magnet: Res<EssenceMagnet>,
+        mut start_upgrades: ResMut<LevelStartUpgrades>,
     ) {
         if let Some(event) = event_reader.iter().next() {
             current_level.0 = event.0;
+            // Upgrades picked up so far are kept even if the next level is restarted
+            start_upgrades.capacity = capacity.clone();
+            start_upgrades.magnet = magnet.clone();
             cmd.insert_resource(LevelSelection::Index(current_level.0));
             for entity in &q_despawn {
                 cmd.entity(entity).despawn_recursive();
@@ -246,6 +290,10 @@ impl Plugin {
         q_level: Query<Entity, With<Handle<LdtkLevel>>>,
         q_despawn: Query<Entity, With<NotFromLevel>>,
         mut essences: ResMut<EssenceCounts>,
+        mut capacity: ResMut<EssenceCapacity>,
+        mut magnet: ResMut<EssenceMagnet>,
+        start_upgrades: Res<LevelStartUpgrades>,
+        mut hotbar: ResMut<PotionHotbar>,
     ) {
         if !event_reader.is_empty() {
             cmd.entity(q_level.single()).insert(Respawn);
@@ -253,6 +301,9 @@ impl Plugin {
                 cmd.entity(entity).despawn_recursive();
             }
             *essences = EssenceCounts::default();
+            *capacity = start_upgrades.capacity.clone();
+            *magnet = start_upgrades.magnet.clone();
+            *hotbar = PotionHotbar::default();
         }
     }
 
@@ -261,11 +312,21 @@ impl Plugin {
         event_reader: EventReader<Reset>,
         mut current_level: ResMut<CurrentLevel>,
         mut essences: ResMut<EssenceCounts>,
+        mut capacity: ResMut<EssenceCapacity>,
+        mut magnet: ResMut<EssenceMagnet>,
+        mut start_upgrades: ResMut<LevelStartUpgrades>,
+        mut score: ResMut<Score>,
+        mut hotbar: ResMut<PotionHotbar>,
     ) {
         if !event_reader.is_empty() {
             current_level.0 = 0;
             cmd.insert_resource(LevelSelection::Index(current_level.0));
             *essences = EssenceCounts::default();
+            *capacity = EssenceCapacity::default();
+            *magnet = EssenceMagnet::default();
+            *start_upgrades = LevelStartUpgrades::default();
+            *score = Score::default();
+            *hotbar = PotionHotbar::default();
         }
     }
 
@@ -309,6 +370,7 @@ impl bevy::app::Plugin for Plugin {
             .register_ldtk_int_cell::<WalkableBundle>(2)
             .register_ldtk_entity::<StairEntity>("Stairs")
             .register_ldtk_entity::<GameEndEntity>("GameEnd")
+            .register_ldtk_entity::<AltarEntity>("Altar")
             .add_event::<ChangeLevel>()
             .add_event::<RestartLevel>()
             .add_event::<Reset>();
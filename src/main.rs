@@ -1,17 +1,28 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod altar;
 mod consts;
+mod damage_numbers;
+mod drops;
 mod enemy;
 mod essence;
 mod game_ui;
+mod healing;
 mod health;
+mod health_bars;
+mod hit_feedback;
 mod hitbox;
 mod hitstun;
 mod homing;
+mod hotbar;
 mod level;
 mod main_menu;
 mod player;
 mod potion;
+mod preview;
+mod reaction;
+mod recipe;
+mod ron_asset;
 mod status;
 mod utils;
 
@@ -23,6 +34,9 @@ use bevy::{prelude::*, render::texture::ImageSettings};
 use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
 use iyes_loopless::prelude::*;
+use serde::Deserialize;
+
+use essence::Overflow;
 
 #[derive(Component)]
 pub struct MainCamera;
@@ -42,7 +56,7 @@ pub enum PauseState {
     Unpaused,
 }
 
-#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[derive(Component, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Deserialize)]
 pub enum Element {
     Fire,
     Water,
@@ -51,6 +65,62 @@ pub enum Element {
     Earth,
 }
 
+/// Ruleset picked on the main menu
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum GameMode {
+    #[default]
+    Standard,
+    Arcade,
+}
+
+impl GameMode {
+    pub fn next(self) -> Self {
+        match self {
+            GameMode::Standard => GameMode::Arcade,
+            GameMode::Arcade => GameMode::Standard,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::Standard => "Standard",
+            GameMode::Arcade => "Arcade",
+        }
+    }
+
+    /// Seconds dropped essence lasts before it expires, if it expires at all
+    pub fn essence_lifetime(&self, element: Element) -> Option<f32> {
+        match self {
+            GameMode::Standard => None,
+            GameMode::Arcade => Some(match element {
+                Element::Lightning => 6.0,
+                Element::Earth => 14.0,
+                _ => 10.0,
+            }),
+        }
+    }
+
+    /// What essence collected into a full pouch turns into
+    pub fn overflow(&self) -> Overflow {
+        match self {
+            GameMode::Standard => Overflow::Heal,
+            GameMode::Arcade => Overflow::Score,
+        }
+    }
+}
+
+impl Element {
+    pub fn color(&self) -> Color {
+        match self {
+            Element::Fire => Color::rgb_u8(232, 89, 45),
+            Element::Water => Color::rgb_u8(60, 126, 232),
+            Element::Wind => Color::rgb_u8(170, 232, 200),
+            Element::Lightning => Color::rgb_u8(245, 224, 66),
+            Element::Earth => Color::rgb_u8(140, 98, 57),
+        }
+    }
+}
+
 fn main() {
     let mut app = App::new();
     app.insert_resource(RapierConfiguration {
@@ -66,6 +136,7 @@ fn main() {
     })
     .insert_resource(ImageSettings::default_nearest())
     .insert_resource(ClearColor(Color::rgb_u8(14, 14, 14)))
+    .init_resource::<GameMode>()
     .add_loopless_state(GameState::MainMenu)
     .add_loopless_state(PauseState::Unpaused)
     .add_plugins(DefaultPlugins)
@@ -75,12 +146,22 @@ fn main() {
     .add_plugin(level::Plugin)
     .add_plugin(player::Plugin)
     .add_plugin(potion::Plugin)
+    .add_plugin(hotbar::Plugin)
+    .add_plugin(preview::Plugin)
+    .add_plugin(recipe::Plugin)
+    .add_plugin(reaction::Plugin)
     .add_plugin(enemy::Plugin)
     .add_plugin(hitbox::Plugin)
+    .add_plugin(hit_feedback::Plugin)
     .add_plugin(health::Plugin)
+    .add_plugin(healing::Plugin)
+    .add_plugin(damage_numbers::Plugin)
+    .add_plugin(health_bars::Plugin)
     .add_plugin(status::Plugin)
     .add_plugin(homing::Plugin)
     .add_plugin(essence::Plugin)
+    .add_plugin(drops::Plugin)
+    .add_plugin(altar::Plugin)
     .add_plugin(main_menu::Plugin)
     .add_plugin(hitstun::Plugin)
     .add_plugin(game_ui::Plugin)
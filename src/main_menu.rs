@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use crate::GameState;
+use crate::{damage_numbers::DamageNumbers, hitbox::FactionRelations, GameMode, GameState};
 use iyes_loopless::prelude::*;
 
 #[derive(Component)]
@@ -9,10 +9,41 @@ pub struct Root;
 #[derive(Component)]
 pub struct BeginButton;
 
+#[derive(Component)]
+pub struct ModeButton;
+
+#[derive(Component)]
+pub struct DamageNumbersButton;
+
+fn damage_numbers_label(enabled: bool) -> &'static str {
+    if enabled {
+        "Damage numbers: On"
+    } else {
+        "Damage numbers: Off"
+    }
+}
+
+#[derive(Component)]
+pub struct FriendlyFireButton;
+
+fn friendly_fire_label(enabled: bool) -> &'static str {
+    if enabled {
+        "Friendly fire: On"
+    } else {
+        "Friendly fire: Off"
+    }
+}
+
 pub struct Plugin;
 
 impl Plugin {
-    fn init(mut cmd: Commands, assets: Res<AssetServer>) {
+    fn init(
+        mut cmd: Commands,
+        assets: Res<AssetServer>,
+        mode: Res<GameMode>,
+        damage_numbers: Res<DamageNumbers>,
+        relations: Res<FactionRelations>,
+    ) {
         cmd.spawn_bundle(NodeBundle {
             style: Style {
                 size: Size {
@@ -52,6 +83,93 @@ impl Plugin {
                 ..default()
             })
             .insert(BeginButton);
+            root.spawn_bundle(ButtonBundle {
+                style: Style {
+                    size: Size {
+                        width: Val::Px(256.0),
+                        height: Val::Px(48.0),
+                    },
+                    margin: UiRect::all(Val::Px(8.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                color: Color::NONE.into(),
+                ..default()
+            })
+            .insert(ModeButton)
+            .with_children(|button| {
+                button.spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        mode.name(),
+                        TextStyle {
+                            font: assets.load("fonts/DejaVuSansMono.ttf"),
+                            font_size: 32.0,
+                            color: Color::WHITE,
+                        },
+                        default(),
+                    ),
+                    ..default()
+                });
+            });
+            root.spawn_bundle(ButtonBundle {
+                style: Style {
+                    size: Size {
+                        width: Val::Px(384.0),
+                        height: Val::Px(40.0),
+                    },
+                    margin: UiRect::all(Val::Px(4.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                color: Color::NONE.into(),
+                ..default()
+            })
+            .insert(DamageNumbersButton)
+            .with_children(|button| {
+                button.spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        damage_numbers_label(**damage_numbers),
+                        TextStyle {
+                            font: assets.load("fonts/DejaVuSansMono.ttf"),
+                            font_size: 24.0,
+                            color: Color::WHITE,
+                        },
+                        default(),
+                    ),
+                    ..default()
+                });
+            });
+            root.spawn_bundle(ButtonBundle {
+                style: Style {
+                    size: Size {
+                        width: Val::Px(384.0),
+                        height: Val::Px(40.0),
+                    },
+                    margin: UiRect::all(Val::Px(4.0)),
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                color: Color::NONE.into(),
+                ..default()
+            })
+            .insert(FriendlyFireButton)
+            .with_children(|button| {
+                button.spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        friendly_fire_label(relations.friendly_fire),
+                        TextStyle {
+                            font: assets.load("fonts/DejaVuSansMono.ttf"),
+                            font_size: 24.0,
+                            color: Color::WHITE,
+                        },
+                        default(),
+                    ),
+                    ..default()
+                });
+            });
         });
     }
 
@@ -74,12 +192,83 @@ impl Plugin {
             }
         }
     }
+
+    fn handle_mode_click(
+        q_button: Query<(&Interaction, &Children), (Changed<Interaction>, With<ModeButton>)>,
+        mut q_text: Query<&mut Text>,
+        mouse: Res<Input<MouseButton>>,
+        mut mode: ResMut<GameMode>,
+    ) {
+        if mouse.just_released(MouseButton::Left) {
+            for (button, children) in &q_button {
+                if button == &Interaction::Hovered {
+                    *mode = mode.next();
+                    for child in children {
+                        if let Ok(mut text) = q_text.get_mut(*child) {
+                            text.sections[0].value = mode.name().to_string();
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_damage_numbers_click(
+        q_button: Query<
+            (&Interaction, &Children),
+            (Changed<Interaction>, With<DamageNumbersButton>),
+        >,
+        mut q_text: Query<&mut Text>,
+        mouse: Res<Input<MouseButton>>,
+        mut damage_numbers: ResMut<DamageNumbers>,
+    ) {
+        if mouse.just_released(MouseButton::Left) {
+            for (button, children) in &q_button {
+                if button == &Interaction::Hovered {
+                    **damage_numbers = !**damage_numbers;
+                    for child in children {
+                        if let Ok(mut text) = q_text.get_mut(*child) {
+                            text.sections[0].value =
+                                damage_numbers_label(**damage_numbers).to_string();
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_friendly_fire_click(
+        q_button: Query<
+            (&Interaction, &Children),
+            (Changed<Interaction>, With<FriendlyFireButton>),
+        >,
+        mut q_text: Query<&mut Text>,
+        mouse: Res<Input<MouseButton>>,
+        mut relations: ResMut<FactionRelations>,
+    ) {
+        if mouse.just_released(MouseButton::Left) {
+            for (button, children) in &q_button {
+                if button == &Interaction::Hovered {
+                    relations.friendly_fire = !relations.friendly_fire;
+                    for child in children {
+                        if let Ok(mut text) = q_text.get_mut(*child) {
+                            text.sections[0].value =
+                                friendly_fire_label(relations.friendly_fire).to_string();
+                        }
+                    }
+                }
+            }
+        }
+    }
 }
 
 impl bevy::app::Plugin for Plugin {
     fn build(&self, app: &mut App) {
         app.add_enter_system(GameState::MainMenu, Self::init)
             .add_exit_system(GameState::MainMenu, Self::cleanup)
-            .add_system(Self::handle_play_click.run_in_state(GameState::MainMenu));
+            .add_system(Self::handle_play_click.run_in_state(GameState::MainMenu))
+            .add_system(Self::handle_mode_click.run_in_state(GameState::MainMenu))
+            .add_system(Self::handle_damage_numbers_click.run_in_state(GameState::MainMenu))
+            .add_system(Self::handle_friendly_fire_click.run_in_state(GameState::MainMenu));
     }
 }
@@ -5,11 +5,14 @@ use bevy_ecs_ldtk::prelude::*;
 use bevy_rapier2d::prelude::*;
 use iyes_loopless::prelude::*;
 
-use crate::essence::{Essence, EssenceCounts};
+use crate::altar::AltarOpen;
+use crate::drops::DropTables;
+use crate::essence::{spawn_essence, EssenceCounts};
 use crate::game_ui::{DeathText, PauseText};
-use crate::health::{Dead, Health, HealthChange};
+use crate::health::{DamageKind, DamageSource, Dead, Health, HealthChange, IFrames, Shield};
+use crate::hitbox::Faction;
 use crate::hitstun::HitstunTimer;
-use crate::level::NotFromLevel;
+use crate::hotbar::PotionHotbar;
 use crate::potion::{PotionBrewData, PotionBrewState, PotionBrewUi};
 use crate::utils::{MousePosition, TimeScale};
 use crate::{consts::*, Element, Enemy, GameState, PauseState};
@@ -45,6 +48,9 @@ pub struct PlayerBundle {
     locked: LockedAxes,
     hitstun: HitstunTimer,
     health: Health,
+    faction: Faction,
+    iframes: IFrames,
+    shield: Shield,
     #[bundle]
     spritesheet: SpriteSheetBundle,
 }
@@ -91,11 +97,15 @@ impl LdtkEntity for PlayerBundle {
                 filters: ENEMY_COLLISION_GROUP
                     | WALL_COLLISION_GROUP
                     | ESSENCE_COLLISION_GROUP
+                    | PLAYER_ATTACK_COLLISION_GROUP
                     | ENEMY_ATTACK_COLLISION_GROUP,
             },
             locked: LockedAxes::ROTATION_LOCKED,
             hitstun: HitstunTimer(Timer::from_seconds(0.0, false)),
-            health: Health::new(250.0),
+            health: Health::new(250.0).with_regen(PLAYER_HEALTH_REGEN),
+            faction: Faction::Player,
+            iframes: IFrames::new(PLAYER_IFRAME_TIME),
+            shield: Shield::new(PLAYER_SHIELD),
         }
     }
 }
@@ -312,6 +322,7 @@ impl Plugin {
         mouse_pos: Res<MousePosition>,
         mut q_player: Query<
             (
+                Entity,
                 &Transform,
                 &Collider,
                 &mut TextureAtlasSprite,
@@ -323,14 +334,17 @@ impl Plugin {
         mut kick_event: EventWriter<Kicked>,
         mut health_event: EventWriter<HealthChange>,
         brew_state: Res<PotionBrewState>,
+        altar_open: Res<AltarOpen>,
     ) {
-        if *brew_state != PotionBrewState::Inactive {
+        // Trades at an altar are clicked with the same button
+        if *brew_state != PotionBrewState::Inactive || **altar_open {
             return;
         }
-        let (player, collider, mut sprite, mut timer) = match q_player.get_single_mut() {
-            Ok(v) => v,
-            Err(_) => return,
-        };
+        let (player_entity, player, collider, mut sprite, mut timer) =
+            match q_player.get_single_mut() {
+                Ok(v) => v,
+                Err(_) => return,
+            };
 
         if mouse_buttons.just_pressed(MouseButton::Left) && sprite.index < PLAYER_KICK_ANIM_OFFSET {
             let pos = player.translation.truncate();
@@ -366,6 +380,13 @@ impl Plugin {
                 health_event.send(HealthChange {
                     target: entity,
                     amount: -10.0,
+                    element: None,
+                    source: Some(DamageSource {
+                        entity: Some(player_entity),
+                        kind: DamageKind::Kick,
+                    }),
+                    ignore_iframes: false,
+                    hit: None,
                 });
             }
         }
@@ -374,6 +395,10 @@ impl Plugin {
     fn init_throw(
         mouse_pos: Res<MousePosition>,
         mouse_buttons: Res<Input<MouseButton>>,
+        gamepads: Res<Gamepads>,
+        gamepad_axes: Res<Axis<GamepadAxis>>,
+        gamepad_buttons: Res<Input<GamepadButton>>,
+        player_direction: Res<PlayerDirection>,
         q_player: Query<&Transform, (With<Player>, Without<PotionBrewUi>)>,
         mut q_brew_ui: Query<&mut Transform, (Without<Player>, With<PotionBrewUi>)>,
 
@@ -384,18 +409,48 @@ impl Plugin {
             Ok(v) => v,
             Err(_) => return,
         };
-        if mouse_buttons.just_pressed(MouseButton::Right) {
-            let pos = player.translation.truncate();
-            let throw_dir = (mouse_pos.truncate() - pos).normalize_or_zero();
-            let mut brew_ui_transform = q_brew_ui.single_mut();
+        let gamepad = gamepads.iter().copied().find(|gamepad| {
+            gamepad_buttons.just_pressed(GamepadButton::new(
+                *gamepad,
+                GamepadButtonType::RightTrigger2,
+            ))
+        });
+        let pos = player.translation.truncate();
+
+        let target = if mouse_buttons.just_pressed(MouseButton::Right) {
+            mouse_pos.truncate()
+        } else if let Some(gamepad) = gamepad {
+            // Aim with the right stick, falling back to the way the player last moved
+            let axis = |ty| {
+                gamepad_axes
+                    .get(GamepadAxis::new(gamepad, ty))
+                    .unwrap_or(0.0)
+            };
+            let stick = Vec2::new(
+                axis(GamepadAxisType::RightStickX),
+                axis(GamepadAxisType::RightStickY),
+            );
+            let aim = if stick.length() > GAMEPAD_AIM_DEADZONE {
+                stick.normalize()
+            } else if **player_direction != IVec2::ZERO {
+                player_direction.as_vec2().normalize()
+            } else {
+                Vec2::NEG_Y
+            };
+            pos + aim * GAMEPAD_THROW_DISTANCE
+        } else {
+            return;
+        };
 
-            brew_ui_transform.translation.x = mouse_pos.x;
-            brew_ui_transform.translation.y = mouse_pos.y;
+        let throw_dir = (target - pos).normalize_or_zero();
+        let mut brew_ui_transform = q_brew_ui.single_mut();
 
-            brew_data.direction = throw_dir;
-            brew_data.position = mouse_pos.truncate();
-            *brew_state = PotionBrewState::Active;
-        }
+        brew_ui_transform.translation.x = target.x;
+        brew_ui_transform.translation.y = target.y;
+
+        brew_data.direction = throw_dir;
+        brew_data.position = target;
+        *brew_state = PotionBrewState::Active;
     }
 
     fn handle_kick(
@@ -411,6 +466,7 @@ impl Plugin {
             ),
             With<Enemy>,
         >,
+        drop_tables: Res<DropTables>,
     ) {
         for event in event_reader.iter() {
             if let Ok((entity, element, transform, mut hitstun_timer, extracted)) =
@@ -423,17 +479,13 @@ impl Plugin {
 
                 if extracted.is_none() {
                     cmd.entity(entity).insert(Extracted);
-                    for _ in 0..fastrand::u8(2..=4) {
-                        let mut transform = transform.compute_transform();
-                        let offset =
-                            Vec2::new((fastrand::f32() - 0.5) * 8.0, (fastrand::f32() - 0.5) * 8.0);
-                        transform.translation += offset.extend(0.0);
-                        cmd.spawn_bundle(SpatialBundle {
-                            transform,
-                            ..default()
-                        })
-                        .insert_bundle((*element, Essence, NotFromLevel));
-                    }
+                    let (min, max) = drop_tables.extract(*element);
+                    spawn_essence(
+                        &mut cmd,
+                        *element,
+                        transform,
+                        fastrand::u32(min..=max.max(min)),
+                    );
                 }
                 hitstun_timer.set_duration(Duration::from_secs_f32(PLAYER_KICK_HITSTUN_SECS));
                 hitstun_timer.reset();
@@ -447,12 +499,14 @@ impl Plugin {
         mut q_death_text: Query<&mut Style, (With<DeathText>, Without<PauseText>)>,
         mut q_pause_text: Query<&mut Style, (Without<DeathText>, With<PauseText>)>,
         mut essences: ResMut<EssenceCounts>,
+        mut hotbar: ResMut<PotionHotbar>,
     ) {
         if !q_dead_player.is_empty() {
             cmd.insert_resource(NextState(PauseState::Paused));
             q_death_text.single_mut().display = Display::Flex;
             q_pause_text.single_mut().display = Display::None;
             *essences = EssenceCounts::default();
+            *hotbar = PotionHotbar::default();
         }
     }
 }
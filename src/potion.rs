@@ -1,42 +1,84 @@
-use bevy::{ecs::system::EntityCommands, prelude::*, sprite::Anchor};
+use bevy::{input::mouse::MouseWheel, prelude::*};
 use bevy_rapier2d::prelude::*;
 use iyes_loopless::prelude::*;
 
 use crate::{
     consts::*,
-    essence::EssenceCounts,
-    hitbox::{
-        DamageOnce, DamagePeriodic, DirectedForce, Falloff, Hitbox, Hitstun, RadialForce,
-        RadialImpulse, StatusEffect, Single,
-    },
-    homing::Homing,
+    essence::{EssenceCapacity, EssenceCounts},
+    health::Shield,
+    hitbox::FactionRelations,
+    hotbar::PotionHotbar,
     level::NotFromLevel,
     player::Player,
-    status::Effect,
-    utils::{
-        DespawnTimer, Digits, ElementIconAtlases, MousePosition, TimeIndependent, TimeScale,
-        UniformAnim, UniformAnimOnce,
-    },
+    recipe::PotionRecipes,
+    utils::{Digits, ElementIconAtlases, MousePosition, TimeIndependent, TimeScale, UniformAnim},
     Element, GameState, PauseState,
 };
 
+/// Keys that pick a brew wheel sector directly, in element order
+const BREW_KEYS: [KeyCode; 5] = [
+    KeyCode::Key1,
+    KeyCode::Key2,
+    KeyCode::Key3,
+    KeyCode::Key4,
+    KeyCode::Key5,
+];
+
 #[derive(Component)]
 pub struct PotionBrewUi;
 
 #[derive(Component)]
-pub struct PotionUiSelect1;
+pub struct PotionUiSelect(usize);
+
+/// A brewed potion
+#[derive(Component, Clone)]
+pub struct PotionType {
+    pub contents: Vec<Element>,
+    /// Raised by spending surplus essence of an ingredient
+    pub tier: u32,
+}
 
+/// One digit of an element's essence count, counting places from the right
 #[derive(Component)]
-pub struct PotionUiSelect2;
+pub struct EssenceCounter(Element, u32);
 
 #[derive(Component, Clone, Copy)]
-pub struct PotionType(Element, Element);
+struct ExplodePosition(Vec2);
 
+/// Walls a thrown potion can still bounce off before it bursts
 #[derive(Component)]
-pub struct EssenceCounter(Element);
+struct Bounces(u32);
 
-#[derive(Component, Clone, Copy)]
-struct ExplodePosition(Vec2);
+/// What a thrown potion does once it reaches its target or runs out of bounces
+#[derive(Component, Clone, Copy, Default, PartialEq)]
+pub enum ThrowMode {
+    #[default]
+    Impact,
+    /// Waits where it lands until detonated
+    Sticky,
+    /// Bursts a while after landing
+    Fused,
+}
+
+impl ThrowMode {
+    pub fn next(self) -> Self {
+        match self {
+            ThrowMode::Impact => ThrowMode::Sticky,
+            ThrowMode::Sticky => ThrowMode::Fused,
+            ThrowMode::Fused => ThrowMode::Impact,
+        }
+    }
+}
+
+/// A sticky or fused potion that has come to rest, with the velocity it landed with
+#[derive(Component)]
+struct Landed(Velocity);
+
+#[derive(Component)]
+struct Fuse(Timer);
+
+#[derive(Component)]
+struct FuseCountdown;
 
 #[derive(Bundle)]
 pub struct PotionBundle {
@@ -49,6 +91,8 @@ pub struct PotionBundle {
     collision_group: CollisionGroups,
     active_events: ActiveEvents,
     explode_pos: ExplodePosition,
+    bounces: Bounces,
+    mode: ThrowMode,
     sensor: Sensor,
     nfl: NotFromLevel,
 }
@@ -57,7 +101,23 @@ pub struct PotionBundle {
 pub struct PotionBrewData {
     pub direction: Vec2,
     pub position: Vec2,
-    contents: (Option<Element>, Option<Element>),
+    pub contents: Vec<Element>,
+    /// Extra essence spent on ingredients already in the brew, one per tier
+    pub surplus: Vec<Element>,
+    /// Element under the cursor for the next slot, if any
+    pub hovered: Option<Element>,
+    pub bounces: u32,
+    pub mode: ThrowMode,
+}
+
+/// Number of ingredients that go into each potion
+#[derive(Deref, DerefMut)]
+pub struct BrewSlots(pub usize);
+
+impl Default for BrewSlots {
+    fn default() -> Self {
+        BrewSlots(BREW_SLOTS)
+    }
 }
 
 #[derive(Default, PartialEq)]
@@ -67,621 +127,93 @@ pub enum PotionBrewState {
     Inactive,
 }
 
-pub struct ThrowPotion(pub Element, pub Element);
-
-struct PotionExplode {
-    potion_type: PotionType,
-    transform: Transform,
-    velocity: Velocity,
-}
-
-fn fire_fire(
-    spawned: &mut EntityCommands,
-    assets: &Res<AssetServer>,
-    atlases: &mut ResMut<Assets<TextureAtlas>>,
-) {
-    spawned
-        .insert_bundle((
-            TextureAtlasSprite::default(),
-            {
-                let tex = assets.load("fire_fire.png");
-                atlases.add(TextureAtlas::from_grid(tex, Vec2::splat(64.0), 6, 1))
-            },
-            UniformAnim(Timer::from_seconds(0.05, true)),
-            DespawnTimer(Timer::from_seconds(0.3, false)),
-        ))
-        .with_children(|parent| {
-            parent
-                .spawn_bundle(SpatialBundle::default())
-                .insert_bundle((
-                    Collider::ball(FIRE_FIRE_RADIUS),
-                    CollisionGroups {
-                        memberships: PLAYER_ATTACK_COLLISION_GROUP,
-                        filters: ENEMY_COLLISION_GROUP,
-                    },
-                    ActiveEvents::COLLISION_EVENTS,
-                    Sensor,
-                    Hitbox,
-                    Hitstun(FIRE_FIRE_HITSTUN),
-                    RadialImpulse::new(FIRE_FIRE_IMPULSE, Falloff::none()),
-                    DamageOnce::new(FIRE_FIRE_DAMAGE, Falloff::none()),
-                    DespawnTimer(Timer::from_seconds(0.1, false)),
-                ));
-        });
-}
-
-fn water_water(
-    spawned: &mut EntityCommands,
-    assets: &Res<AssetServer>,
-    atlases: &mut ResMut<Assets<TextureAtlas>>,
-    velocity: &Velocity,
-) {
-    let direction = velocity.linvel.normalize();
-    let tex = assets.load("water_water.png");
-    let atlas = atlases.add(TextureAtlas::from_grid(tex, Vec2::new(8.0, 16.0), 12, 1));
-    spawned
-        .insert_bundle((
-            Velocity {
-                linvel: direction * WATER_WATER_SPEED,
-                angvel: 0.0,
-            },
-            RigidBody::KinematicVelocityBased,
-            DespawnTimer(Timer::from_seconds(WATER_WATER_DURATION, false)),
-        ))
-        .with_children(|parent| {
-            parent
-                .spawn_bundle(SpatialBundle {
-                    transform: Transform {
-                        rotation: Quat::from_axis_angle(
-                            Vec3::Z,
-                            direction.y.atan2(direction.x) + std::f32::consts::PI / 2.0,
-                        ),
-                        ..default()
-                    },
-                    ..default()
-                })
-                .insert_bundle((
-                    Collider::cuboid(WATER_WATER_HALF_WIDTH, WATER_WATER_HALF_HEIGHT),
-                    CollisionGroups {
-                        memberships: PLAYER_ATTACK_COLLISION_GROUP,
-                        filters: ENEMY_COLLISION_GROUP,
-                    },
-                    ActiveEvents::COLLISION_EVENTS,
-                    Sensor,
-                    Hitbox,
-                    DirectedForce::new(direction * WATER_WATER_FORCE),
-                ));
-
-            for i in -8..=8 {
-                parent
-                    .spawn_bundle(SpriteSheetBundle {
-                        texture_atlas: atlas.clone(),
-                        transform: Transform {
-                            translation: Vec3::new(
-                                -direction.y * 4.0 * i as f32,
-                                direction.x * 4.0 * i as f32,
-                                -direction.x * 0.1e-5 * i as f32,
-                            ),
-                            ..default()
-                        },
-                        ..default()
-                    })
-                    .insert(UniformAnim(Timer::from_seconds(0.05, true)));
-            }
-        });
+pub struct ThrowPotion {
+    pub potion: PotionType,
+    pub direction: Vec2,
+    pub target: Vec2,
+    pub bounces: u32,
+    pub mode: ThrowMode,
 }
 
-fn wind_wind(
-    spawned: &mut EntityCommands,
-    assets: &Res<AssetServer>,
-    atlases: &mut ResMut<Assets<TextureAtlas>>,
-) {
-    spawned
-        .insert_bundle((
-            TextureAtlasSprite::default(),
-            {
-                let tex = assets.load("wind_wind.png");
-                atlases.add(TextureAtlas::from_grid(tex, Vec2::splat(64.0), 4, 1))
-            },
-            UniformAnim(Timer::from_seconds(0.05, true)),
-            DespawnTimer(Timer::from_seconds(WIND_WIND_DURATION, false)),
-        ))
-        .with_children(|parent| {
-            parent
-                .spawn_bundle(SpatialBundle::default())
-                .insert_bundle((
-                    Collider::ball(WIND_WIND_RADIUS),
-                    CollisionGroups {
-                        memberships: PLAYER_ATTACK_COLLISION_GROUP,
-                        filters: ENEMY_COLLISION_GROUP,
-                    },
-                    ActiveEvents::COLLISION_EVENTS,
-                    Sensor,
-                    Hitbox,
-                    RadialForce::new(
-                        -WIND_WIND_FORCE,
-                        Falloff::new(
-                            WIND_WIND_MAX_FALLOFF,
-                            WIND_WIND_FALLOFF_START,
-                            WIND_WIND_FALLOFF_END,
-                        ),
-                    ),
-                ));
-        });
+pub struct PotionExplode {
+    pub potion_type: PotionType,
+    pub transform: Transform,
+    pub velocity: Velocity,
 }
 
-fn lightning_lightning(
-    spawned: &mut EntityCommands,
-    assets: &Res<AssetServer>,
-    atlases: &mut ResMut<Assets<TextureAtlas>>,
-) {
-    spawned
-        .insert_bundle((
-            TextureAtlasSprite {
-                anchor: Anchor::BottomCenter,
+fn spawn_brew_displays(root: &mut ChildBuilder, slots: usize) {
+    for i in 0..slots {
+        root.spawn_bundle(SpriteSheetBundle {
+            transform: Transform {
+                translation: Vec3::new(
+                    BREW_UI_ICON_SIZE * (2.0 * i as f32 - (slots - 1) as f32),
+                    0.0,
+                    0.1,
+                ),
                 ..default()
             },
-            {
-                let tex = assets.load("lightning_lightning.png");
-                atlases.add(TextureAtlas::from_grid(tex, Vec2::new(16.0, 64.0), 4, 1))
-            },
-            UniformAnim(Timer::from_seconds(0.05, true)),
-            DespawnTimer(Timer::from_seconds(0.25, false)),
-        ))
-        .with_children(|parent| {
-            parent
-                .spawn_bundle(SpatialBundle::default())
-                .insert_bundle((
-                    Collider::ball(LIGHTNING_LIGHTNING_RADIUS),
-                    CollisionGroups {
-                        memberships: PLAYER_ATTACK_COLLISION_GROUP,
-                        filters: ENEMY_COLLISION_GROUP,
-                    },
-                    ActiveEvents::COLLISION_EVENTS,
-                    Sensor,
-                    Hitbox,
-                    Hitstun(LIGHTNING_LIGHTNING_HITSTUN),
-                    DamageOnce::new(
-                        LIGHTNING_LIGHTNING_DAMAGE,
-                        Falloff::new(
-                            LIGHTNING_LIGHTNING_MAX_FALLOFF,
-                            LIGHTNING_LIGHTNING_FALLOFF_START,
-                            LIGHTNING_LIGHTNING_FALLOFF_END,
-                        ),
-                    ),
-                    DespawnTimer(Timer::from_seconds(0.1, false)),
-                ));
-        });
-}
-
-fn earth_earth(
-    spawned: &mut EntityCommands,
-    assets: &Res<AssetServer>,
-    atlases: &mut ResMut<Assets<TextureAtlas>>,
-) {
-    spawned
-        .insert_bundle((
-            TextureAtlasSprite::default(),
-            {
-                let tex = assets.load("earth_earth.png");
-                atlases.add(TextureAtlas::from_grid(tex, Vec2::splat(32.0), 40, 1))
-            },
-            UniformAnim(Timer::from_seconds(0.05, true)),
-            DespawnTimer(Timer::from_seconds(EARTH_EARTH_DURATION, false)),
-        ))
-        .with_children(|parent| {
-            parent
-                .spawn_bundle(SpatialBundle::default())
-                .insert_bundle((
-                    Collider::ball(EARTH_EARTH_RADIUS),
-                    CollisionGroups {
-                        memberships: WALL_COLLISION_GROUP,
-                        filters: ENEMY_COLLISION_GROUP
-                            | PLAYER_COLLISION_GROUP
-                            | PLAYER_ATTACK_COLLISION_GROUP
-                            | ENEMY_ATTACK_COLLISION_GROUP,
-                    },
-                ));
-        });
-}
-
-fn fire_water(
-    spawned: &mut EntityCommands,
-    assets: &Res<AssetServer>,
-    atlases: &mut ResMut<Assets<TextureAtlas>>,
-) {
-    //TODO: art
-    spawned
-        .insert_bundle((
-            TextureAtlasSprite::default(),
-            {
-                let tex = assets.load("fire_water.png");
-                atlases.add(TextureAtlas::from_grid(tex, Vec2::splat(64.0), 5, 1))
-            },
-            UniformAnim(Timer::from_seconds(0.1, true)),
-            DespawnTimer(Timer::from_seconds(FIRE_WATER_DURATION, false)),
-        ))
-        .with_children(|parent| {
-            parent
-                .spawn_bundle(SpatialBundle::default())
-                .insert_bundle((
-                    Collider::ball(FIRE_WATER_RADIUS),
-                    CollisionGroups {
-                        memberships: PLAYER_ATTACK_COLLISION_GROUP,
-                        filters: ENEMY_COLLISION_GROUP,
-                    },
-                    ActiveEvents::COLLISION_EVENTS,
-                    Sensor,
-                    Hitbox,
-                    RadialForce::new(
-                        FIRE_WATER_FORCE,
-                        Falloff::new(
-                            FIRE_WATER_MAX_FALLOFF,
-                            FIRE_WATER_FALLOFF_START,
-                            FIRE_WATER_FALLOFF_END,
-                        ),
-                    ),
-                ));
-        });
-}
-
-fn fire_wind(
-    spawned: &mut EntityCommands,
-    assets: &Res<AssetServer>,
-    atlases: &mut ResMut<Assets<TextureAtlas>>,
-) {
-    spawned
-        .insert_bundle((
-            TextureAtlasSprite::default(),
-            {
-                let tex = assets.load("fire_wind.png");
-                atlases.add(TextureAtlas::from_grid(tex, Vec2::splat(96.0), 3, 1))
-            },
-            UniformAnim(Timer::from_seconds(0.05, true)),
-            DespawnTimer(Timer::from_seconds(FIRE_WIND_DURATION, false)),
-        ))
-        .with_children(|parent| {
-            parent
-                .spawn_bundle(SpatialBundle::default())
-                .insert_bundle((
-                    Collider::ball(FIRE_WIND_RADIUS),
-                    CollisionGroups {
-                        memberships: PLAYER_ATTACK_COLLISION_GROUP,
-                        filters: ENEMY_COLLISION_GROUP,
-                    },
-                    ActiveEvents::COLLISION_EVENTS,
-                    Sensor,
-                    Hitbox,
-                    StatusEffect(Effect::OnFire),
-                ));
-        });
-}
-
-fn fire_lightning(
-    spawned: &mut EntityCommands,
-    assets: &Res<AssetServer>,
-    atlases: &mut ResMut<Assets<TextureAtlas>>,
-) {
-    spawned
-        .insert_bundle((
-            TextureAtlasSprite::default(),
-            {
-                let tex = assets.load("fire_lightning.png");
-                atlases.add(TextureAtlas::from_grid(tex, Vec2::splat(16.0), 2, 1))
+            sprite: TextureAtlasSprite {
+                color: Color::rgba(1.0, 1.0, 1.0, 0.5),
+                ..default()
             },
-            UniformAnim(Timer::from_seconds(0.1, true)),
-            DespawnTimer(Timer::from_seconds(0.2, false)),
-        ))
-        .with_children(|parent| {
-            parent
-                .spawn_bundle(SpatialBundle::default())
-                .insert_bundle((
-                    Collider::ball(FIRE_LIGHTNING_RADIUS),
-                    CollisionGroups {
-                        memberships: PLAYER_ATTACK_COLLISION_GROUP,
-                        filters: ENEMY_COLLISION_GROUP,
-                    },
-                    ActiveEvents::COLLISION_EVENTS,
-                    Sensor,
-                    Hitbox,
-                    Single::new(),
-                    StatusEffect(Effect::DelayedExplosion),
-                    DespawnTimer(Timer::from_seconds(FIRE_LIGHTNING_DURATION, false)),
-                ));
-        });
-}
-
-fn fire_earth(
-    spawned: &mut EntityCommands,
-    assets: &Res<AssetServer>,
-    atlases: &mut ResMut<Assets<TextureAtlas>>,
-) {
-    spawned
+            ..default()
+        })
         .insert_bundle((
-            TextureAtlasSprite::default(),
-            {
-                let tex = assets.load("fire_earth.png");
-                atlases.add(TextureAtlas::from_grid(tex, Vec2::splat(96.0), 14, 1))
-            },
             UniformAnim(Timer::from_seconds(0.1, true)),
-            DespawnTimer(Timer::from_seconds(FIRE_EARTH_DURATION, false)),
-        ))
-        .with_children(|parent| {
-            parent
-                .spawn_bundle(SpatialBundle::default())
-                .insert_bundle((
-                    Collider::ball(FIRE_EARTH_RADIUS),
-                    CollisionGroups {
-                        memberships: PLAYER_ATTACK_COLLISION_GROUP,
-                        filters: ENEMY_COLLISION_GROUP,
-                    },
-                    ActiveEvents::COLLISION_EVENTS,
-                    Sensor,
-                    Hitbox,
-                    DamagePeriodic::new(FIRE_EARTH_DAMAGE, Falloff::none(), FIRE_EARTH_TICK),
-                ));
-        });
-}
-
-fn water_wind(
-    spawned: &mut EntityCommands,
-    assets: &Res<AssetServer>,
-    atlases: &mut ResMut<Assets<TextureAtlas>>,
-) {
-    spawned
-        .insert_bundle((
-            Velocity::default(),
-            RigidBody::KinematicVelocityBased,
-            Homing {
-                max_speed: WATER_WIND_CHASE_SPEED,
-            },
-            Sensor,
-            Collider::ball(WATER_WIND_CHASE_RADIUS),
-            ActiveEvents::COLLISION_EVENTS,
-            CollisionGroups {
-                memberships: PLAYER_ATTACK_COLLISION_GROUP,
-                filters: ENEMY_COLLISION_GROUP,
-            },
-            TextureAtlasSprite::default(),
-            {
-                let tex = assets.load("water_wind.png");
-                atlases.add(TextureAtlas::from_grid(tex, Vec2::splat(64.0), 7, 1))
-            },
-            UniformAnim(Timer::from_seconds(0.05, true)),
-            DespawnTimer(Timer::from_seconds(WATER_WIND_DURATION, false)),
-        ))
-        .with_children(|parent| {
-            parent
-                .spawn_bundle(SpatialBundle::default())
-                .insert_bundle((
-                    Collider::ball(WATER_WIND_RADIUS),
-                    CollisionGroups {
-                        memberships: PLAYER_ATTACK_COLLISION_GROUP,
-                        filters: ENEMY_COLLISION_GROUP,
-                    },
-                    ActiveEvents::COLLISION_EVENTS,
-                    Sensor,
-                    Hitbox,
-                    StatusEffect(Effect::Slowed),
-                ));
-        });
+            TimeIndependent,
+            PotionUiSelect(i),
+        ));
+    }
 }
 
-fn water_lightning(
-    spawned: &mut EntityCommands,
-    assets: &Res<AssetServer>,
-    atlases: &mut ResMut<Assets<TextureAtlas>>,
+/// Bursts a potion that has reached the end of its flight, or leaves it lying there
+/// for sticky and fused throws
+fn land(
+    cmd: &mut Commands,
+    event_writer: &mut EventWriter<PotionExplode>,
+    digits: &Digits,
+    entity: Entity,
+    explode: PotionExplode,
+    mode: ThrowMode,
 ) {
-    spawned
-        .insert_bundle((
-            TextureAtlasSprite::default(),
-            {
-                let tex = assets.load("water_lightning.png");
-                atlases.add(TextureAtlas::from_grid(tex, Vec2::splat(96.0), 3, 1))
-            },
-            UniformAnim(Timer::from_seconds(0.05, true)),
-            DespawnTimer(Timer::from_seconds(0.15, false)),
-        ))
-        .with_children(|parent| {
-            parent
-                .spawn_bundle(SpatialBundle::default())
-                .insert_bundle((
-                    Collider::ball(WATER_LIGHTNING_RADIUS),
-                    CollisionGroups {
-                        memberships: PLAYER_ATTACK_COLLISION_GROUP,
-                        filters: ENEMY_COLLISION_GROUP,
-                    },
-                    ActiveEvents::COLLISION_EVENTS,
-                    Sensor,
-                    Hitbox,
-                    StatusEffect(Effect::Shocked),
-                    DespawnTimer(Timer::from_seconds(WATER_LIGHTNING_DURATION, false)),
-                ));
-        });
-}
+    if mode == ThrowMode::Impact {
+        event_writer.send(explode);
+        cmd.entity(entity).despawn_recursive();
+        return;
+    }
 
-fn water_earth(
-    spawned: &mut EntityCommands,
-    assets: &Res<AssetServer>,
-    atlases: &mut ResMut<Assets<TextureAtlas>>,
-    velocity: &Velocity,
-) {
-    let direction = velocity.linvel.normalize();
-    let tex = assets.load("water_earth.png");
-    let atlas = atlases.add(TextureAtlas::from_grid(tex, Vec2::new(16.0, 16.0), 15, 1));
-    spawned
-        .insert(DespawnTimer(Timer::from_seconds(
-            WATER_EARTH_DURATION,
-            false,
-        )))
-        .with_children(|parent| {
+    let mut potion = cmd.entity(entity);
+    potion.insert_bundle((
+        Landed(explode.velocity),
+        RigidBody::Fixed,
+        Velocity::default(),
+        Transform {
+            rotation: Quat::IDENTITY,
+            ..explode.transform
+        },
+    ));
+    if mode == ThrowMode::Fused {
+        potion.insert(Fuse(Timer::from_seconds(POTION_FUSE_TIME, false)));
+        potion.with_children(|parent| {
             parent
-                .spawn_bundle(SpatialBundle {
-                    transform: Transform {
-                        rotation: Quat::from_axis_angle(
-                            Vec3::Z,
-                            direction.y.atan2(direction.x) + std::f32::consts::PI / 2.0,
-                        ),
-                        translation: (direction * WATER_EARTH_HALF_HEIGHT).extend(0.0),
+                .spawn_bundle(SpriteSheetBundle {
+                    texture_atlas: digits.clone_weak(),
+                    sprite: TextureAtlasSprite {
+                        index: POTION_FUSE_TIME.ceil() as usize,
                         ..default()
                     },
+                    transform: Transform::from_xyz(0.0, POTION_FUSE_COUNTDOWN_OFFSET, 1.0),
                     ..default()
                 })
-                .insert_bundle((
-                    Collider::cuboid(WATER_EARTH_HALF_WIDTH, WATER_EARTH_HALF_HEIGHT),
-                    CollisionGroups {
-                        memberships: PLAYER_ATTACK_COLLISION_GROUP,
-                        filters: ENEMY_COLLISION_GROUP,
-                    },
-                    ActiveEvents::COLLISION_EVENTS,
-                    Sensor,
-                    Hitbox,
-                    Hitstun(WATER_EARTH_HITSTUN),
-                    DamagePeriodic::new(WATER_EARTH_DAMAGE, Falloff::none(), WATER_EARTH_TICK),
-                ));
-
-            for i in 0..=1 {
-                for j in 1..=16 {
-                    parent
-                        .spawn_bundle(SpriteSheetBundle {
-                            texture_atlas: atlas.clone(),
-                            transform: Transform {
-                                translation: Vec3::new(
-                                    direction.x * 6.0 * j as f32
-                                        - direction.y * 8.0 * (i as f32 - 0.5),
-                                    direction.y * 6.0 * j as f32
-                                        + direction.x * 8.0 * (i as f32 - 0.5),
-                                    direction.y * 0.1e-5 * j as f32,
-                                ),
-                                ..default()
-                            },
-                            ..default()
-                        })
-                        .insert(UniformAnimOnce(Timer::from_seconds(0.1, true)));
-                }
-            }
-        });
-}
-
-fn wind_lightning(
-    spawned: &mut EntityCommands,
-    assets: &Res<AssetServer>,
-    atlases: &mut ResMut<Assets<TextureAtlas>>,
-) {
-    spawned
-        .insert_bundle((
-            Velocity::default(),
-            RigidBody::KinematicVelocityBased,
-            Homing {
-                max_speed: WIND_LIGHTNING_CHASE_SPEED,
-            },
-            Sensor,
-            Collider::ball(WIND_LIGHTNING_CHASE_RADIUS),
-            ActiveEvents::COLLISION_EVENTS,
-            CollisionGroups {
-                memberships: PLAYER_ATTACK_COLLISION_GROUP,
-                filters: ENEMY_COLLISION_GROUP,
-            },
-            TextureAtlasSprite::default(),
-            {
-                let tex = assets.load("wind_lightning.png");
-                atlases.add(TextureAtlas::from_grid(tex, Vec2::splat(64.0), 6, 1))
-            },
-            UniformAnim(Timer::from_seconds(0.05, true)),
-            DespawnTimer(Timer::from_seconds(WIND_LIGHTNING_DURATION, false)),
-        ))
-        .with_children(|parent| {
-            parent
-                .spawn_bundle(SpatialBundle::default())
-                .insert_bundle((
-                    Collider::ball(WIND_LIGHTNING_RADIUS),
-                    CollisionGroups {
-                        memberships: PLAYER_ATTACK_COLLISION_GROUP,
-                        filters: ENEMY_COLLISION_GROUP,
-                    },
-                    ActiveEvents::COLLISION_EVENTS,
-                    Sensor,
-                    Hitbox,
-                    DamagePeriodic::new(
-                        WIND_LIGHTNING_DAMAGE,
-                        Falloff::none(),
-                        WIND_LIGHTNING_TICK,
-                    ),
-                ));
+                .insert(FuseCountdown);
         });
+    }
 }
 
-fn wind_earth(
-    spawned: &mut EntityCommands,
-    assets: &Res<AssetServer>,
-    atlases: &mut ResMut<Assets<TextureAtlas>>,
-) {
-    spawned
-        .insert_bundle((
-            TextureAtlasSprite::default(),
-            {
-                let tex = assets.load("wind_earth.png");
-                atlases.add(TextureAtlas::from_grid(tex, Vec2::splat(96.0), 3, 1))
-            },
-            UniformAnim(Timer::from_seconds(0.05, true)),
-            DespawnTimer(Timer::from_seconds(0.15, false)),
-        ))
-        .with_children(|parent| {
-            parent
-                .spawn_bundle(SpatialBundle::default())
-                .insert_bundle((
-                    Collider::ball(WIND_EARTH_RADIUS),
-                    CollisionGroups {
-                        memberships: PLAYER_ATTACK_COLLISION_GROUP,
-                        filters: ENEMY_COLLISION_GROUP,
-                    },
-                    ActiveEvents::COLLISION_EVENTS,
-                    Sensor,
-                    Hitbox,
-                    StatusEffect(Effect::Blinded),
-                    DespawnTimer(Timer::from_seconds(WIND_EARTH_DURATION, false)),
-                ));
-        });
-}
-
-fn lightning_earth(
-    spawned: &mut EntityCommands,
-    assets: &Res<AssetServer>,
-    atlases: &mut ResMut<Assets<TextureAtlas>>,
-    rotation: f32,
-) {
-    let tex = assets.load("lightning_earth.png");
-    let atlas = atlases.add(TextureAtlas::from_grid(tex, Vec2::splat(4.0), 4, 1));
-    //TODO: spawned just has initial strike art
-    for i in 0..LIGHTNING_EARTH_COUNT {
-        let rotation = rotation + (std::f32::consts::TAU / LIGHTNING_EARTH_COUNT as f32) * i as f32;
-        spawned.with_children(|parent| {
-            parent
-                .spawn_bundle(SpatialBundle::default())
-                .insert_bundle((
-                    TextureAtlasSprite::default(),
-                    atlas.clone(),
-                    UniformAnim(Timer::from_seconds(0.05, true)),
-                    Velocity {
-                        linvel: Vec2::from_angle(rotation) * LIGHTNING_EARTH_SPEED,
-                        angvel: 0.0,
-                    },
-                    RigidBody::Dynamic,
-                    Collider::ball(LIGHTNING_EARTH_RADIUS),
-                    CollisionGroups {
-                        memberships: PLAYER_ATTACK_COLLISION_GROUP,
-                        filters: ENEMY_COLLISION_GROUP | WALL_COLLISION_GROUP,
-                    },
-                    ActiveEvents::COLLISION_EVENTS,
-                    Hitbox,
-                    Hitstun(LIGHTNING_EARTH_HITSTUN),
-                    DamageOnce::new(LIGHTNING_EARTH_DAMAGE, Falloff::none()),
-                    DespawnTimer(Timer::from_seconds(LIGHTNING_EARTH_DURATION, false)),
-                    LockedAxes::ROTATION_LOCKED,
-                    Ccd::enabled(),
-                ));
-        });
-    }
+/// Direction from the centre of the brew wheel to the middle of a sector
+fn sector_direction(index: usize) -> Vec2 {
+    let angle = std::f32::consts::PI * (1.5 - (2 * index) as f32) / 5.0;
+    Vec2::new(angle.cos(), angle.sin())
 }
 
 pub struct Plugin;
@@ -691,6 +223,7 @@ impl Plugin {
         assets: Res<AssetServer>,
         element_icons: ResMut<ElementIconAtlases>,
         digits: Res<Digits>,
+        brew_slots: Res<BrewSlots>,
     ) {
         cmd.spawn_bundle(SpriteBundle {
             sprite: Sprite {
@@ -707,48 +240,11 @@ impl Plugin {
         })
         .insert(PotionBrewUi)
         .with_children(|root| {
-            root.spawn_bundle(SpriteSheetBundle {
-                transform: Transform {
-                    translation: Vec3::new(-BREW_UI_ICON_SIZE, 0.0, 0.1),
-                    ..default()
-                },
-                sprite: TextureAtlasSprite {
-                    color: Color::rgba(1.0, 1.0, 1.0, 0.5),
-                    ..default()
-                },
-                ..default()
-            })
-            .insert_bundle((
-                UniformAnim(Timer::from_seconds(0.1, true)),
-                TimeIndependent,
-                PotionUiSelect1,
-            ));
-            root.spawn_bundle(SpriteSheetBundle {
-                transform: Transform {
-                    translation: Vec3::new(BREW_UI_ICON_SIZE, 0.0, 0.1),
-                    ..default()
-                },
-                sprite: TextureAtlasSprite {
-                    color: Color::rgba(1.0, 1.0, 1.0, 0.5),
-                    ..default()
-                },
-                ..default()
-            })
-            .insert_bundle((
-                UniformAnim(Timer::from_seconds(0.1, true)),
-                TimeIndependent,
-                PotionUiSelect2,
-            ));
+            spawn_brew_displays(root, **brew_slots);
             for i in 0..5 {
-                let angle = std::f32::consts::PI * (1.5 - (2 * i) as f32) / 5.0;
-
                 root.spawn_bundle(SpatialBundle {
                     transform: Transform {
-                        translation: Vec3::new(
-                            angle.cos() * BREW_UI_ICON_DISTANCE,
-                            angle.sin() * BREW_UI_ICON_DISTANCE,
-                            0.5,
-                        ),
+                        translation: (sector_direction(i) * BREW_UI_ICON_DISTANCE).extend(0.5),
                         ..default()
                     },
                     ..default()
@@ -763,26 +259,32 @@ impl Plugin {
                     TimeIndependent,
                 ))
                 .with_children(|root| {
-                    root.spawn_bundle(SpriteSheetBundle {
-                        texture_atlas: digits.clone_weak(),
-                        transform: Transform {
-                            translation: Vec3::new(0.0, -9.0, 0.0),
-                            ..default()
-                        },
-                        sprite: TextureAtlasSprite {
-                            color: Color::RED,
-                            ..default()
-                        },
-                        ..default()
-                    })
-                    .insert(EssenceCounter(match i {
+                    let element = match i {
                         0 => Element::Fire,
                         1 => Element::Water,
                         2 => Element::Wind,
                         3 => Element::Lightning,
                         4 => Element::Earth,
                         _ => unreachable!(),
-                    }));
+                    };
+                    for place in 0..ESSENCE_COUNTER_DIGITS {
+                        root.spawn_bundle(SpriteSheetBundle {
+                            texture_atlas: digits.clone_weak(),
+                            transform: Transform {
+                                translation: Vec3::new(0.0, -9.0, 0.0),
+                                ..default()
+                            },
+                            sprite: TextureAtlasSprite {
+                                color: Color::RED,
+                                ..default()
+                            },
+                            visibility: Visibility {
+                                is_visible: place == 0,
+                            },
+                            ..default()
+                        })
+                        .insert(EssenceCounter(element, place));
+                    }
                 });
             }
         });
@@ -793,18 +295,17 @@ impl Plugin {
         assets: Res<AssetServer>,
         mut event_reader: EventReader<ThrowPotion>,
         q_player: Query<&Transform, With<Player>>,
-        brew_data: Res<PotionBrewData>,
     ) {
         let player_transform = match q_player.get_single() {
             Ok(v) => v,
             Err(_) => return,
         };
-        for ThrowPotion(e1, e2) in event_reader.iter() {
+        for event in event_reader.iter() {
             cmd.spawn_bundle(PotionBundle {
-                potion_type: PotionType(*e1, *e2),
+                potion_type: event.potion.clone(),
                 rigidbody: RigidBody::Dynamic,
                 velocity: Velocity {
-                    linvel: brew_data.direction * POTION_THROW_SPEED,
+                    linvel: event.direction * POTION_THROW_SPEED,
                     angvel: POTION_SPIN_SPEED,
                 },
                 collider: Collider::ball(2.0),
@@ -821,7 +322,9 @@ impl Plugin {
                     filters: WALL_COLLISION_GROUP,
                 },
                 active_events: ActiveEvents::COLLISION_EVENTS,
-                explode_pos: ExplodePosition(brew_data.position),
+                explode_pos: ExplodePosition(event.target),
+                bounces: Bounces(event.bounces),
+                mode: event.mode,
                 sensor: Sensor,
                 nfl: NotFromLevel,
             });
@@ -830,31 +333,81 @@ impl Plugin {
 
     fn update_brew(
         q_brew_ui: Query<&Transform, With<PotionBrewUi>>,
-        mut q_brew_display1: Query<
-            (&mut TextureAtlasSprite, &mut Handle<TextureAtlas>),
-            (With<PotionUiSelect1>, Without<PotionUiSelect2>),
-        >,
-        mut q_brew_display2: Query<
+        mut q_brew_display: Query<(
+            &mut TextureAtlasSprite,
             &mut Handle<TextureAtlas>,
-            (Without<PotionUiSelect1>, With<PotionUiSelect2>),
-        >,
+            &PotionUiSelect,
+        )>,
         mut event_writer: EventWriter<ThrowPotion>,
         mouse_pos: Res<MousePosition>,
         mouse_buttons: Res<Input<MouseButton>>,
+        keys: Res<Input<KeyCode>>,
         element_icons: ResMut<ElementIconAtlases>,
         mut brew_data: ResMut<PotionBrewData>,
         mut brew_state: ResMut<PotionBrewState>,
         mut counts: ResMut<EssenceCounts>,
+        brew_slots: Res<BrewSlots>,
+        mut hotbar: ResMut<PotionHotbar>,
+        gamepads: Res<Gamepads>,
+        gamepad_axes: Res<Axis<GamepadAxis>>,
+        gamepad_buttons: Res<Input<GamepadButton>>,
     ) {
         if *brew_state != PotionBrewState::Active {
             return;
         }
-        let (mut sprite1, mut handle1) = q_brew_display1.single_mut();
-        let mut handle2 = q_brew_display2.single_mut();
+        let slot = brew_data.contents.len();
+
+        let gamepad = gamepads.iter().next().copied();
+        let stick = gamepad.map_or(Vec2::ZERO, |gamepad| {
+            let axis = |ty| {
+                gamepad_axes
+                    .get(GamepadAxis::new(gamepad, ty))
+                    .unwrap_or(0.0)
+            };
+            Vec2::new(
+                axis(GamepadAxisType::LeftStickX),
+                axis(GamepadAxisType::LeftStickY),
+            )
+        });
+        let gamepad_pressed = |ty| {
+            gamepad.map_or(false, |gamepad| {
+                gamepad_buttons.just_pressed(GamepadButton::new(gamepad, ty))
+            })
+        };
 
-        let relative_mouse_pos = (mouse_pos.0 - q_brew_ui.single().translation).truncate();
+        // Only the mouse can reach past the outer ring, so the stick and brew keys get explicit
+        // cancels instead (Escape cancels too, by pausing)
+        if gamepad_pressed(GamepadButtonType::East) || keys.just_pressed(KeyCode::Back) {
+            *brew_state = PotionBrewState::Inactive;
+            return;
+        }
 
-        let angle = relative_mouse_pos.angle_between(Vec2::NEG_Y) + std::f32::consts::PI;
+        // Every input method picks a point on the wheel, so they all share the deadzone
+        // Holding control spends surplus essence on an ingredient instead of filling a slot
+        let fortify = keys.pressed(KeyCode::LControl)
+            || keys.pressed(KeyCode::RControl)
+            || gamepad_pressed(GamepadButtonType::LeftTrigger2);
+
+        let (relative_pos, confirm) =
+            if let Some(sector) = BREW_KEYS.iter().position(|key| keys.just_pressed(*key)) {
+                (sector_direction(sector) * BREW_UI_ICON_DISTANCE, true)
+            } else if stick.length() * BREW_UI_SIZE / 2.0 > BREW_UI_DEADZONE
+                || gamepad_pressed(GamepadButtonType::South)
+                || gamepad_pressed(GamepadButtonType::LeftTrigger2)
+            {
+                (
+                    stick * BREW_UI_SIZE / 2.0,
+                    gamepad_pressed(GamepadButtonType::South)
+                        || gamepad_pressed(GamepadButtonType::LeftTrigger2),
+                )
+            } else {
+                (
+                    (mouse_pos.0 - q_brew_ui.single().translation).truncate(),
+                    mouse_buttons.just_pressed(MouseButton::Left),
+                )
+            };
+
+        let angle = relative_pos.angle_between(Vec2::NEG_Y) + std::f32::consts::PI;
 
         let index = (angle / (std::f32::consts::TAU / 5.0)) as usize % 5;
         let element = match index {
@@ -866,48 +419,62 @@ impl Plugin {
             _ => unreachable!(),
         };
 
-        if mouse_buttons.just_pressed(MouseButton::Left) {
-            if relative_mouse_pos.length() > BREW_UI_SIZE / 2.0 {
+        if confirm {
+            if relative_pos.length() > BREW_UI_SIZE / 2.0 {
                 *brew_state = PotionBrewState::Inactive;
                 return;
-            } else if relative_mouse_pos.length() > BREW_UI_DEADZONE {
-                if counts[element] != 0 {
+            } else if relative_pos.length() > BREW_UI_DEADZONE {
+                if fortify {
+                    if counts[element] != 0
+                        && brew_data.contents.contains(&element)
+                        && brew_data.surplus.len() < POTION_MAX_TIER
+                    {
+                        *counts.get_mut(&element).unwrap() -= 1;
+                        brew_data.surplus.push(element);
+                    }
+                } else if counts[element] != 0 {
                     *counts.get_mut(&element).unwrap() -= 1;
-                    match brew_data.contents {
-                        (None, None) => {
-                            brew_data.contents.0 = Some(element);
-                            sprite1.color.set_a(1.0);
-                            *handle1 = element_icons[index].clone_weak();
+                    brew_data.contents.push(element);
+                    for (mut sprite, mut handle, select) in &mut q_brew_display {
+                        if select.0 == slot {
+                            sprite.color.set_a(1.0);
+                            *handle = element_icons[index].clone_weak();
                         }
-                        (Some(first), None) => {
-                            brew_data.contents.1 = Some(element);
-                            event_writer.send(ThrowPotion(first, element));
-                            *brew_state = PotionBrewState::Inactive;
+                    }
+                    if brew_data.contents.len() >= **brew_slots {
+                        // Holding shift puts the potion on the hotbar instead, if there's room
+                        let stash = keys.pressed(KeyCode::LShift) || keys.pressed(KeyCode::RShift);
+                        let potion = PotionType {
+                            contents: brew_data.contents.clone(),
+                            tier: brew_data.surplus.len() as u32,
+                        };
+                        if !(stash && hotbar.store(potion.clone())) {
+                            event_writer.send(ThrowPotion {
+                                potion,
+                                direction: brew_data.direction,
+                                target: brew_data.position,
+                                bounces: brew_data.bounces,
+                                mode: brew_data.mode,
+                            });
                         }
-                        _ => unreachable!(),
+                        *brew_state = PotionBrewState::Inactive;
                     }
                 }
             }
         } else {
-            let out_of_zone = relative_mouse_pos.length() > BREW_UI_SIZE / 2.0
-                || relative_mouse_pos.length() < BREW_UI_DEADZONE;
-            match brew_data.contents {
-                (None, None) => {
-                    if out_of_zone {
-                        *handle1 = Handle::<TextureAtlas>::default();
-                    } else {
-                        *handle1 = element_icons[index].clone_weak();
-                        sprite1.color.set_a(0.5);
-                    }
+            let out_of_zone = relative_pos.length() > BREW_UI_SIZE / 2.0
+                || relative_pos.length() < BREW_UI_DEADZONE;
+            brew_data.hovered = (!out_of_zone).then_some(element);
+            for (mut sprite, mut handle, select) in &mut q_brew_display {
+                if select.0 != slot {
+                    continue;
                 }
-                (Some(_), None) => {
-                    if out_of_zone {
-                        *handle2 = Handle::<TextureAtlas>::default();
-                    } else {
-                        *handle2 = element_icons[index].clone_weak();
-                    }
+                if out_of_zone {
+                    *handle = Handle::<TextureAtlas>::default();
+                } else {
+                    *handle = element_icons[index].clone_weak();
+                    sprite.color.set_a(0.5);
                 }
-                _ => unreachable!(),
             }
         }
     }
@@ -917,15 +484,9 @@ impl Plugin {
         mut brew_data: ResMut<PotionBrewData>,
         mut time_scale: ResMut<TimeScale>,
         mut q_brew_ui: Query<&mut Visibility, With<PotionBrewUi>>,
-        mut q_brew_display1: Query<
-            &mut Handle<TextureAtlas>,
-            (With<PotionUiSelect1>, Without<PotionUiSelect2>),
-        >,
-        mut q_brew_display2: Query<
-            &mut Handle<TextureAtlas>,
-            (Without<PotionUiSelect1>, With<PotionUiSelect2>),
-        >,
+        mut q_brew_display: Query<&mut Handle<TextureAtlas>, With<PotionUiSelect>>,
         mut counts: ResMut<EssenceCounts>,
+        brew_slots: Res<BrewSlots>,
     ) {
         if brew_state.is_changed() && *brew_state == PotionBrewState::Active {
             **time_scale = 0.01;
@@ -934,59 +495,223 @@ impl Plugin {
             **time_scale = 1.0;
             q_brew_ui.single_mut().is_visible = false;
 
-            if brew_data.contents.1.is_none() {
-                if let Some(element) = brew_data.contents.0 {
-                    *counts.get_mut(&element).unwrap() += 1;
+            // Cancelled before the last slot was filled, so give everything back
+            if brew_data.contents.len() < **brew_slots {
+                for element in brew_data.contents.iter().chain(&brew_data.surplus) {
+                    *counts.get_mut(element).unwrap() += 1;
                 }
             }
-            brew_data.contents = (None, None);
-            *q_brew_display1.single_mut() = Handle::<TextureAtlas>::default();
-            *q_brew_display2.single_mut() = Handle::<TextureAtlas>::default();
+            brew_data.contents.clear();
+            brew_data.surplus.clear();
+            brew_data.hovered = None;
+            for mut handle in &mut q_brew_display {
+                *handle = Handle::<TextureAtlas>::default();
+            }
+        }
+    }
+
+    fn update_throw_options(
+        mut wheel_events: EventReader<MouseWheel>,
+        keys: Res<Input<KeyCode>>,
+        gamepads: Res<Gamepads>,
+        gamepad_buttons: Res<Input<GamepadButton>>,
+        brew_state: Res<PotionBrewState>,
+        mut brew_data: ResMut<PotionBrewData>,
+    ) {
+        if *brew_state != PotionBrewState::Active {
+            wheel_events.clear();
+            return;
+        }
+        let gamepad_pressed = |ty| {
+            gamepads
+                .iter()
+                .any(|gamepad| gamepad_buttons.just_pressed(GamepadButton::new(*gamepad, ty)))
+        };
+
+        if keys.just_pressed(KeyCode::Tab) || gamepad_pressed(GamepadButtonType::North) {
+            brew_data.mode = brew_data.mode.next();
+        }
+
+        let mut change = 0.0;
+        for event in wheel_events.iter() {
+            change += event.y.signum();
+        }
+        if gamepad_pressed(GamepadButtonType::RightTrigger) {
+            change += 1.0;
+        }
+        if gamepad_pressed(GamepadButtonType::LeftTrigger) {
+            change -= 1.0;
+        }
+        if change != 0.0 {
+            brew_data.bounces = (brew_data.bounces as i32 + change as i32)
+                .clamp(0, POTION_MAX_BOUNCES as i32) as u32;
         }
     }
 
+    fn update_brew_slots(
+        mut cmd: Commands,
+        brew_slots: Res<BrewSlots>,
+        q_brew_ui: Query<Entity, With<PotionBrewUi>>,
+        q_brew_display: Query<Entity, With<PotionUiSelect>>,
+    ) {
+        if !brew_slots.is_changed() {
+            return;
+        }
+        let brew_ui = match q_brew_ui.get_single() {
+            Ok(v) => v,
+            Err(_) => return,
+        };
+        for entity in &q_brew_display {
+            cmd.entity(entity).despawn_recursive();
+        }
+        cmd.entity(brew_ui).with_children(|root| {
+            spawn_brew_displays(root, **brew_slots);
+        });
+    }
+
     fn potion_explode(
         mut cmd: Commands,
         mut event_reader: EventReader<CollisionEvent>,
-        q_potion: Query<(Entity, &PotionType, &Transform, &Velocity, &ExplodePosition)>,
+        mut q_potion: Query<
+            (
+                Entity,
+                &PotionType,
+                &Transform,
+                &mut Velocity,
+                &mut ExplodePosition,
+                &mut Bounces,
+                &ThrowMode,
+            ),
+            Without<Landed>,
+        >,
+        rapier_ctx: Res<RapierContext>,
+        digits: Res<Digits>,
         mut event_writer: EventWriter<PotionExplode>,
     ) {
         for event in event_reader.iter() {
-            match event {
-                CollisionEvent::Started(e1, e2, _) => {
-                    if let Ok((_, &potion_type, &transform, &velocity, _)) = q_potion.get(*e1) {
-                        event_writer.send(PotionExplode {
-                            potion_type,
-                            transform,
-                            velocity,
-                        });
-                        cmd.entity(*e1).despawn_recursive();
-                    } else if let Ok((_, &potion_type, &transform, &velocity, _)) =
-                        q_potion.get(*e2)
-                    {
-                        event_writer.send(PotionExplode {
-                            potion_type,
-                            transform,
-                            velocity,
-                        });
-                        cmd.entity(*e2).despawn_recursive();
-                    } else {
-                        continue;
-                    }
+            let potion = match event {
+                CollisionEvent::Started(e1, _, _) if q_potion.contains(*e1) => *e1,
+                CollisionEvent::Started(_, e2, _) if q_potion.contains(*e2) => *e2,
+                _ => continue,
+            };
+            let (_, potion_type, &transform, mut velocity, mut position, mut bounces, &mode) =
+                q_potion.get_mut(potion).unwrap();
+
+            if bounces.0 > 0 {
+                // Potions are sensors, so find the wall normal by backing up and looking ahead
+                let direction = velocity.linvel.normalize_or_zero();
+                let filter = QueryFilter::new().groups(InteractionGroups {
+                    memberships: PLAYER_ATTACK_COLLISION_GROUP,
+                    filter: WALL_COLLISION_GROUP,
+                });
+                if let Some((_, hit)) = rapier_ctx.cast_ray_and_get_normal(
+                    transform.translation.truncate() - direction * POTION_BOUNCE_PROBE,
+                    direction,
+                    POTION_BOUNCE_PROBE * 2.0,
+                    true,
+                    filter,
+                ) {
+                    bounces.0 -= 1;
+                    velocity.linvel -= 2.0 * velocity.linvel.dot(hit.normal) * hit.normal;
+                    // Mirroring the target keeps the distance left to travel the same
+                    position.0 -= 2.0 * (position.0 - hit.point).dot(hit.normal) * hit.normal;
+                    continue;
                 }
-                _ => (),
             }
+
+            land(
+                &mut cmd,
+                &mut event_writer,
+                &digits,
+                potion,
+                PotionExplode {
+                    potion_type: potion_type.clone(),
+                    transform,
+                    velocity: *velocity,
+                },
+                mode,
+            );
         }
 
-        for (entity, &potion_type, &transform, &velocity, &position) in &q_potion {
+        for (entity, potion_type, &transform, &velocity, &position, _, &mode) in &q_potion {
             let direction = position.0 - transform.translation.truncate();
             if velocity.linvel.dot(direction) <= 0.0 {
+                land(
+                    &mut cmd,
+                    &mut event_writer,
+                    &digits,
+                    entity,
+                    PotionExplode {
+                        potion_type: potion_type.clone(),
+                        transform,
+                        velocity,
+                    },
+                    mode,
+                );
+            }
+        }
+    }
+
+    fn detonate(
+        mut cmd: Commands,
+        keys: Res<Input<KeyCode>>,
+        gamepads: Res<Gamepads>,
+        gamepad_buttons: Res<Input<GamepadButton>>,
+        q_landed: Query<(Entity, &PotionType, &Transform, &Landed, &ThrowMode)>,
+        mut event_writer: EventWriter<PotionExplode>,
+    ) {
+        let gamepad_pressed = gamepads.iter().any(|gamepad| {
+            gamepad_buttons.just_pressed(GamepadButton::new(*gamepad, GamepadButtonType::West))
+        });
+        if !keys.just_pressed(KeyCode::Space) && !gamepad_pressed {
+            return;
+        }
+        for (entity, potion_type, &transform, landed, &mode) in &q_landed {
+            if mode != ThrowMode::Sticky {
+                continue;
+            }
+            event_writer.send(PotionExplode {
+                potion_type: potion_type.clone(),
+                transform,
+                velocity: landed.0,
+            });
+            cmd.entity(entity).despawn_recursive();
+        }
+    }
+
+    fn tick_fuses(
+        mut cmd: Commands,
+        mut q_fused: Query<(
+            Entity,
+            &PotionType,
+            &Transform,
+            &Landed,
+            &mut Fuse,
+            &Children,
+        )>,
+        mut q_countdown: Query<&mut TextureAtlasSprite, With<FuseCountdown>>,
+        time: Res<Time>,
+        time_scale: Res<TimeScale>,
+        mut event_writer: EventWriter<PotionExplode>,
+    ) {
+        let delta = time.delta().mul_f32(**time_scale);
+        for (entity, potion_type, &transform, landed, mut fuse, children) in &mut q_fused {
+            fuse.0.tick(delta);
+            if fuse.0.finished() {
                 event_writer.send(PotionExplode {
-                    potion_type,
+                    potion_type: potion_type.clone(),
                     transform,
-                    velocity,
+                    velocity: landed.0,
                 });
                 cmd.entity(entity).despawn_recursive();
+                continue;
+            }
+
+            let seconds_left = (fuse.0.duration() - fuse.0.elapsed()).as_secs_f32().ceil() as usize;
+            for &child in children {
+                if let Ok(mut sprite) = q_countdown.get_mut(child) {
+                    sprite.index = seconds_left.min(9);
+                }
             }
         }
     }
@@ -996,79 +721,53 @@ impl Plugin {
         mut event_reader: EventReader<PotionExplode>,
         assets: Res<AssetServer>,
         mut atlases: ResMut<Assets<TextureAtlas>>,
+        recipes: Res<PotionRecipes>,
+        relations: Res<FactionRelations>,
+        mut q_player: Query<(&GlobalTransform, &mut Shield), With<Player>>,
     ) {
         for event in event_reader.iter() {
             let transform = event.transform;
             let potion_type = &event.potion_type;
             let velocity = event.velocity;
             let rotation = transform.rotation.to_euler(EulerRot::XYZ).2;
+
+            let (recipe, amplify) = match recipes.resolve(&potion_type.contents) {
+                Some(v) => v,
+                None => {
+                    warn!("No potion recipe for {:?}", potion_type.contents);
+                    continue;
+                }
+            };
+            let amplify = amplify * recipes.potency(potion_type.tier);
+
             let mut spawned = cmd.spawn_bundle(SpatialBundle {
                 transform: Transform {
                     rotation: Quat::IDENTITY,
+                    scale: Vec3::new(amplify.radius, amplify.radius, 1.0),
                     ..transform
                 },
                 ..default()
             });
             spawned.insert(NotFromLevel);
 
-            {
-                use Element::*;
-                match (potion_type.0, potion_type.1) {
-                    (Fire, Fire) => {
-                        fire_fire(&mut spawned, &assets, &mut atlases);
-                    }
-                    (Water, Water) => {
-                        water_water(&mut spawned, &assets, &mut atlases, &velocity);
-                    }
-                    (Wind, Wind) => {
-                        wind_wind(&mut spawned, &assets, &mut atlases);
-                    }
-                    (Lightning, Lightning) => {
-                        lightning_lightning(&mut spawned, &assets, &mut atlases);
-                    }
-                    (Earth, Earth) => {
-                        earth_earth(&mut spawned, &assets, &mut atlases);
-                        //big rock just sprouts and blocks stuff
-                    }
-                    (Fire, Water) | (Water, Fire) => {
-                        fire_water(&mut spawned, &assets, &mut atlases);
-                        //steam geyser - shoves away
-                    }
-                    (Fire, Wind) | (Wind, Fire) => {
-                        fire_wind(&mut spawned, &assets, &mut atlases);
-                        //sets things on fire (big area, dot)
-                    }
-                    (Fire, Lightning) | (Lightning, Fire) => {
-                        fire_lightning(&mut spawned, &assets, &mut atlases);
-                        //delayed explosion, sticks to 1 enemy
-                    }
-                    (Fire, Earth) | (Earth, Fire) => {
-                        fire_earth(&mut spawned, &assets, &mut atlases);
-                        //Damaging lava puddle
-                    }
-                    (Water, Wind) | (Wind, Water) => {
-                        water_wind(&mut spawned, &assets, &mut atlases);
-                        //homing rain cloud - slows enemies under it
-                    }
-                    (Water, Lightning) | (Lightning, Water) => {
-                        water_lightning(&mut spawned, &assets, &mut atlases);
-                        //Affected enemies shoot lightning at nearby enemies
-                    }
-                    (Water, Earth) | (Earth, Water) => {
-                        water_earth(&mut spawned, &assets, &mut atlases, &velocity);
-                        //grows vines on the ground, damaging enemies that walk through
-                    }
-                    (Wind, Lightning) | (Lightning, Wind) => {
-                        wind_lightning(&mut spawned, &assets, &mut atlases);
-                        //homing storm cloud
-                    }
-                    (Wind, Earth) | (Earth, Wind) => {
-                        wind_earth(&mut spawned, &assets, &mut atlases);
-                        //dust storm - blinds
-                    }
-                    (Lightning, Earth) | (Earth, Lightning) => {
-                        lightning_earth(&mut spawned, &assets, &mut atlases, rotation);
-                        //lightning strikes at location, sparks go through ground back to player
+            recipe.spawn(
+                &mut spawned,
+                &assets,
+                &mut atlases,
+                &velocity,
+                rotation,
+                amplify,
+                &relations,
+            );
+
+            if let Some(grant) = &recipe.shield {
+                if let Ok((player_transform, mut shield)) = q_player.get_single_mut() {
+                    let distance = player_transform
+                        .translation()
+                        .truncate()
+                        .distance(transform.translation.truncate());
+                    if distance <= grant.radius * amplify.radius {
+                        shield.grant(grant.amount, grant.duration * amplify.duration);
                     }
                 }
             }
@@ -1076,16 +775,28 @@ impl Plugin {
     }
 
     fn update_counter(
-        mut q_counter: Query<(&mut TextureAtlasSprite, &EssenceCounter)>,
+        mut q_counter: Query<(
+            &mut TextureAtlasSprite,
+            &mut Transform,
+            &mut Visibility,
+            &EssenceCounter,
+        )>,
         amounts: Res<EssenceCounts>,
+        capacity: Res<EssenceCapacity>,
     ) {
-        if amounts.is_changed() {
-            for (mut sprite, essence) in &mut q_counter {
-                let count = *amounts.get(&essence.0).unwrap() as usize;
-                sprite.index = count;
+        if amounts.is_changed() || capacity.is_changed() {
+            for (mut sprite, mut transform, mut visibility, counter) in &mut q_counter {
+                let count = amounts[counter.0];
+                // Always show the ones digit, and only as many others as the count needs
+                let width = (count.max(1) as f32).log10() as u32 + 1;
+                visibility.is_visible = counter.1 < width;
+                sprite.index = (count / 10u32.pow(counter.1) % 10) as usize;
+                transform.translation.x =
+                    ((width - 1) as f32 / 2.0 - counter.1 as f32) * ESSENCE_COUNTER_DIGIT_WIDTH;
+
                 if count == 0 {
                     sprite.color = Color::RED;
-                } else if count == 5 {
+                } else if count >= capacity[counter.0] {
                     sprite.color = Color::YELLOW;
                 } else {
                     sprite.color = Color::WHITE;
@@ -1108,12 +819,29 @@ impl bevy::app::Plugin for Plugin {
                     .run_in_state(GameState::InGame)
                     .run_not_in_state(PauseState::Paused),
             )
-            .add_system(Self::update_brew.run_in_state(GameState::InGame))
+            .add_system(
+                Self::update_brew
+                    .run_in_state(GameState::InGame)
+                    .label("update_brew"),
+            )
+            .add_system(Self::update_throw_options.run_in_state(GameState::InGame))
+            .add_system(Self::update_brew_slots.run_in_state(GameState::InGame))
             .add_system(Self::potion_explode.run_in_state(GameState::InGame))
+            .add_system(
+                Self::detonate
+                    .run_in_state(GameState::InGame)
+                    .run_not_in_state(PauseState::Paused),
+            )
+            .add_system(
+                Self::tick_fuses
+                    .run_in_state(GameState::InGame)
+                    .run_not_in_state(PauseState::Paused),
+            )
             .add_system(Self::potion_effect.run_in_state(GameState::InGame))
             .add_system(Self::update_counter.run_in_state(GameState::InGame))
             .init_resource::<PotionBrewData>()
             .init_resource::<PotionBrewState>()
+            .init_resource::<BrewSlots>()
             .add_event::<ThrowPotion>()
             .add_event::<PotionExplode>();
     }
@@ -7,7 +7,7 @@ pub struct Plugin;
 
 impl Plugin {
     fn preload(mut cmd: Commands, assets: Res<AssetServer>) {
-        const FILES: [&'static str; 32] = [
+        const FILES: [&'static str; 33] = [
             "blinded.png",
             "bottle.png",
             "delayed_explosion.png",
@@ -19,6 +19,7 @@ impl Plugin {
             "fire_lightning.png",
             "fire_water.png",
             "fire_wind.png",
+            "fonts/DejaVuSansMono.ttf",
             "lightning_earth.png",
             "lightning_elemental.png",
             "lightning_elemental_attack.png",
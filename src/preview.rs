@@ -0,0 +1,367 @@
+use bevy::{
+    prelude::*,
+    text::{HorizontalAlign, VerticalAlign},
+    utils::HashMap,
+};
+use bevy_rapier2d::prelude::*;
+use iyes_loopless::prelude::*;
+
+use crate::{
+    consts::*,
+    player::Player,
+    potion::{BrewSlots, PotionBrewData, PotionBrewState, PotionBrewUi, ThrowMode},
+    recipe::{DiscoveredPotions, PotionRecipes},
+    Element, GameState,
+};
+
+/// One dot of the dotted throw line
+#[derive(Component)]
+pub struct TrajectoryDot;
+
+/// Outline of the area the brewed potion will cover
+#[derive(Component)]
+pub struct LandingGhost;
+
+/// Name of the potion the hovered sector would brew
+#[derive(Component)]
+pub struct CombinationName;
+
+/// Icon of the potion the hovered sector would brew
+#[derive(Component)]
+pub struct CombinationIcon;
+
+/// Potency tier, throw mode and bounce count picked for the potion being brewed
+#[derive(Component)]
+pub struct ThrowOptionsLabel;
+
+fn spawn_label(cmd: &mut Commands, assets: &AssetServer, marker: impl Component) {
+    cmd.spawn_bundle(Text2dBundle {
+        text: Text::with_section(
+            "",
+            TextStyle {
+                font: assets.load("fonts/DejaVuSansMono.ttf"),
+                font_size: PREVIEW_FONT_SIZE,
+                color: Color::WHITE,
+            },
+            TextAlignment {
+                vertical: VerticalAlign::Center,
+                horizontal: HorizontalAlign::Center,
+            },
+        ),
+        // Rendered large and scaled down so it stays sharp under the zoomed in camera
+        transform: Transform::from_scale(Vec3::splat(PREVIEW_TEXT_SCALE)),
+        visibility: Visibility { is_visible: false },
+        ..default()
+    })
+    .insert(marker);
+}
+
+/// Ingredients the potion will have if the hovered element is picked, if that finishes it
+fn pending_potion(brew_data: &PotionBrewData, brew_slots: usize) -> Option<Vec<Element>> {
+    let mut contents = brew_data.contents.clone();
+    contents.extend(brew_data.hovered);
+    (contents.len() >= brew_slots).then_some(contents)
+}
+
+pub struct Plugin;
+
+impl Plugin {
+    fn init(mut cmd: Commands, assets: Res<AssetServer>) {
+        for _ in 0..PREVIEW_DOT_COUNT {
+            cmd.spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba(1.0, 1.0, 1.0, PREVIEW_ALPHA),
+                    custom_size: Some(Vec2::splat(PREVIEW_DOT_SIZE)),
+                    ..default()
+                },
+                visibility: Visibility { is_visible: false },
+                ..default()
+            })
+            .insert(TrajectoryDot);
+        }
+
+        cmd.spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(1.0, 1.0, 1.0, PREVIEW_ALPHA),
+                ..default()
+            },
+            texture: assets.load("select_circle.png"),
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert(LandingGhost);
+
+        spawn_label(&mut cmd, &assets, CombinationName);
+        spawn_label(&mut cmd, &assets, ThrowOptionsLabel);
+
+        cmd.spawn_bundle(SpriteSheetBundle {
+            sprite: TextureAtlasSprite {
+                custom_size: Some(Vec2::splat(PREVIEW_ICON_SIZE)),
+                ..default()
+            },
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert(CombinationIcon);
+    }
+
+    fn update(
+        q_player: Query<&Transform, With<Player>>,
+        mut q_dots: Query<
+            (&mut Transform, &mut Visibility),
+            (With<TrajectoryDot>, Without<Player>, Without<LandingGhost>),
+        >,
+        mut q_ghost: Query<
+            (&mut Transform, &mut Visibility, &mut Sprite),
+            (With<LandingGhost>, Without<Player>),
+        >,
+        rapier_ctx: Res<RapierContext>,
+        recipes: Res<PotionRecipes>,
+        brew_data: Res<PotionBrewData>,
+        brew_state: Res<PotionBrewState>,
+        brew_slots: Res<BrewSlots>,
+    ) {
+        let (mut ghost_transform, mut ghost_visibility, mut ghost_sprite) =
+            match q_ghost.get_single_mut() {
+                Ok(v) => v,
+                Err(_) => return,
+            };
+        let player = match q_player.get_single() {
+            Ok(v) if *brew_state == PotionBrewState::Active => v,
+            _ => {
+                ghost_visibility.is_visible = false;
+                for (_, mut visibility) in &mut q_dots {
+                    visibility.is_visible = false;
+                }
+                return;
+            }
+        };
+
+        // Follow the potion's path, bouncing off walls until it runs out of bounces or distance
+        let filter = QueryFilter::new().groups(InteractionGroups {
+            memberships: PLAYER_ATTACK_COLLISION_GROUP,
+            filter: WALL_COLLISION_GROUP,
+        });
+        let mut start = player.translation.truncate();
+        let mut direction = (brew_data.position - start).normalize_or_zero();
+        let mut remaining = brew_data.position.distance(start);
+        let mut segments = vec![];
+        for bounce in 0..=brew_data.bounces {
+            match rapier_ctx.cast_ray_and_get_normal(start, direction, remaining, true, filter) {
+                Some((_, hit)) => {
+                    segments.push((start, direction, hit.toi));
+                    if bounce == brew_data.bounces {
+                        break;
+                    }
+                    remaining -= hit.toi;
+                    direction -= 2.0 * direction.dot(hit.normal) * hit.normal;
+                    start = hit.point + hit.normal * 0.01;
+                }
+                None => {
+                    segments.push((start, direction, remaining));
+                    break;
+                }
+            }
+        }
+        let landing = segments
+            .last()
+            .map_or(start, |&(start, direction, length)| {
+                start + direction * length
+            });
+
+        let mut dots = q_dots.iter_mut();
+        let mut along = 0.0;
+        for (start, direction, length) in segments {
+            while along < length {
+                let (mut transform, mut visibility) = match dots.next() {
+                    Some(v) => v,
+                    None => break,
+                };
+                visibility.is_visible = true;
+                transform.translation = (start + direction * along).extend(PREVIEW_Z);
+                along += PREVIEW_DOT_SPACING;
+            }
+            along -= length;
+        }
+        for (_, mut visibility) in dots {
+            visibility.is_visible = false;
+        }
+
+        // Only show the effect once the next pick would finish the potion
+        let pending = pending_potion(&brew_data, **brew_slots);
+        match pending
+            .as_deref()
+            .and_then(|contents| recipes.resolve(contents))
+        {
+            Some((recipe, amplify)) => {
+                let amplify = amplify * recipes.potency(brew_data.surplus.len() as u32);
+                let radius = recipe.radius() * amplify.radius;
+                ghost_visibility.is_visible = radius > 0.0;
+                ghost_sprite.custom_size = Some(Vec2::splat(radius * 2.0));
+                ghost_transform.translation = landing.extend(PREVIEW_Z);
+            }
+            None => ghost_visibility.is_visible = false,
+        }
+    }
+
+    fn update_combination(
+        q_brew_ui: Query<&Transform, With<PotionBrewUi>>,
+        mut q_name: Query<
+            (&mut Text, &mut Transform, &mut Visibility),
+            (With<CombinationName>, Without<PotionBrewUi>),
+        >,
+        mut q_icon: Query<
+            (&mut Handle<TextureAtlas>, &mut Transform, &mut Visibility),
+            (
+                With<CombinationIcon>,
+                Without<PotionBrewUi>,
+                Without<CombinationName>,
+            ),
+        >,
+        assets: Res<AssetServer>,
+        mut atlases: ResMut<Assets<TextureAtlas>>,
+        recipes: Res<PotionRecipes>,
+        discovered: Res<DiscoveredPotions>,
+        brew_data: Res<PotionBrewData>,
+        brew_state: Res<PotionBrewState>,
+        brew_slots: Res<BrewSlots>,
+        mut icon_atlases: Local<HashMap<String, Handle<TextureAtlas>>>,
+    ) {
+        let (mut text, mut name_transform, mut name_visibility) = match q_name.get_single_mut() {
+            Ok(v) => v,
+            Err(_) => return,
+        };
+        let (mut icon, mut icon_transform, mut icon_visibility) = match q_icon.get_single_mut() {
+            Ok(v) => v,
+            Err(_) => return,
+        };
+        let wheel = match q_brew_ui.get_single() {
+            Ok(v) => v.translation.truncate(),
+            Err(_) => return,
+        };
+
+        let pending = match pending_potion(&brew_data, **brew_slots) {
+            Some(v) if *brew_state == PotionBrewState::Active => v,
+            _ => {
+                name_visibility.is_visible = false;
+                icon_visibility.is_visible = false;
+                return;
+            }
+        };
+        let recipe = match recipes.resolve(&pending) {
+            Some((recipe, _)) => recipe,
+            None => {
+                name_visibility.is_visible = false;
+                icon_visibility.is_visible = false;
+                return;
+            }
+        };
+
+        let below = wheel - Vec2::new(0.0, BREW_UI_SIZE / 2.0);
+        icon_transform.translation =
+            (below - Vec2::new(0.0, PREVIEW_ICON_SIZE / 2.0)).extend(999.0);
+        name_transform.translation = (below
+            - Vec2::new(
+                0.0,
+                PREVIEW_ICON_SIZE + PREVIEW_FONT_SIZE * PREVIEW_TEXT_SCALE / 2.0,
+            ))
+        .extend(999.0);
+        name_visibility.is_visible = true;
+
+        if !discovered.contains(&pending) {
+            text.sections[0].value = "???".to_string();
+            icon_visibility.is_visible = false;
+            return;
+        }
+
+        // Brews past their recipe's size are amplified versions of it
+        let extra = pending.len() - recipe.ingredients.len();
+        text.sections[0].value = if extra > 0 {
+            format!("{} +{}", recipe.name, extra)
+        } else {
+            recipe.name.clone()
+        };
+
+        match recipe.icon() {
+            Some(sheet) => {
+                *icon = icon_atlases
+                    .entry(sheet.path.clone())
+                    .or_insert_with(|| sheet.atlas(&assets, &mut atlases))
+                    .clone();
+                icon_visibility.is_visible = true;
+            }
+            None => icon_visibility.is_visible = false,
+        }
+    }
+
+    fn update_throw_options(
+        q_brew_ui: Query<&Transform, With<PotionBrewUi>>,
+        mut q_label: Query<
+            (&mut Text, &mut Transform, &mut Visibility),
+            (With<ThrowOptionsLabel>, Without<PotionBrewUi>),
+        >,
+        brew_data: Res<PotionBrewData>,
+        brew_state: Res<PotionBrewState>,
+    ) {
+        let (mut text, mut transform, mut visibility) = match q_label.get_single_mut() {
+            Ok(v) => v,
+            Err(_) => return,
+        };
+        let wheel = match q_brew_ui.get_single() {
+            Ok(v) => v.translation.truncate(),
+            Err(_) => return,
+        };
+
+        let mut options = vec![];
+        if !brew_data.surplus.is_empty() {
+            options.push(format!("Tier {}", brew_data.surplus.len()));
+        }
+        match brew_data.mode {
+            ThrowMode::Impact => (),
+            ThrowMode::Sticky => options.push("Sticky".to_string()),
+            ThrowMode::Fused => options.push("Fused".to_string()),
+        }
+        match brew_data.bounces {
+            0 => (),
+            1 => options.push("1 bounce".to_string()),
+            n => options.push(format!("{} bounces", n)),
+        }
+
+        visibility.is_visible = *brew_state == PotionBrewState::Active && !options.is_empty();
+        text.sections[0].value = options.join(", ");
+        transform.translation = (wheel
+            + Vec2::new(
+                0.0,
+                (BREW_UI_SIZE + PREVIEW_FONT_SIZE * PREVIEW_TEXT_SCALE) / 2.0,
+            ))
+        .extend(999.0);
+    }
+
+    fn cleanup(
+        mut cmd: Commands,
+        q_preview: Query<
+            Entity,
+            Or<(
+                With<TrajectoryDot>,
+                With<LandingGhost>,
+                With<CombinationName>,
+                With<CombinationIcon>,
+                With<ThrowOptionsLabel>,
+            )>,
+        >,
+    ) {
+        for entity in &q_preview {
+            cmd.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+impl bevy::app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_enter_system(GameState::InGame, Self::init)
+            .add_exit_system(GameState::InGame, Self::cleanup)
+            .add_system(Self::update.run_in_state(GameState::InGame))
+            .add_system(Self::update_combination.run_in_state(GameState::InGame))
+            .add_system(Self::update_throw_options.run_in_state(GameState::InGame));
+    }
+}
@@ -0,0 +1,163 @@
+use bevy::{prelude::*, utils::HashSet};
+use bevy_rapier2d::prelude::*;
+use iyes_loopless::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    potion::{PotionExplode, PotionType},
+    recipe::PotionRecipes,
+    Element, GameState,
+};
+
+/// What a live potion effect is made of, as far as reactions are concerned
+#[derive(Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ZoneTag {
+    Wet,
+    Burning,
+    Gust,
+    Charged,
+    Molten,
+}
+
+/// Which of the two zones a reaction happens at
+#[derive(Deserialize, Clone, Copy, Default, PartialEq)]
+pub enum ReactionSite {
+    Trigger,
+    #[default]
+    Target,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct Reaction {
+    pub trigger: ZoneTag,
+    pub target: ZoneTag,
+    #[serde(default)]
+    pub remove_trigger: bool,
+    #[serde(default)]
+    pub remove_target: bool,
+    /// Potion effect to spawn, as if a potion with these ingredients burst there
+    #[serde(default)]
+    pub spawn: Option<Vec<Element>>,
+    #[serde(default)]
+    pub at: ReactionSite,
+}
+
+/// A live potion effect that can react with others overlapping it
+#[derive(Component)]
+pub struct PotionZone {
+    pub tags: Vec<ZoneTag>,
+}
+
+/// Whether any of a zone's hitbox colliders touches one belonging to `other`
+fn overlapping(
+    rapier_ctx: &RapierContext,
+    q_hitboxes: &Query<(&Collider, &GlobalTransform, &Parent)>,
+    children: &Children,
+    other: Entity,
+) -> bool {
+    let belongs_to_other = |entity| {
+        q_hitboxes
+            .get(entity)
+            .map_or(false, |(_, _, parent)| parent.get() == other)
+    };
+    let filter = QueryFilter {
+        predicate: Some(&belongs_to_other),
+        ..default()
+    };
+
+    children
+        .iter()
+        .filter_map(|&child| q_hitboxes.get(child).ok())
+        .any(|(collider, transform, _)| {
+            let (_, rotation, translation) = transform.to_scale_rotation_translation();
+            let mut found = false;
+            rapier_ctx.intersections_with_shape(
+                translation.truncate(),
+                rotation.to_euler(EulerRot::XYZ).2,
+                collider,
+                filter,
+                |_| {
+                    found = true;
+                    false
+                },
+            );
+            found
+        })
+}
+
+pub struct Plugin;
+
+impl Plugin {
+    fn react(
+        mut cmd: Commands,
+        q_zones: Query<(Entity, &PotionZone, &GlobalTransform, &Children)>,
+        q_hitboxes: Query<(&Collider, &GlobalTransform, &Parent)>,
+        rapier_ctx: Res<RapierContext>,
+        recipes: Res<PotionRecipes>,
+        mut event_writer: EventWriter<PotionExplode>,
+        mut reacted: Local<HashSet<(Entity, Entity)>>,
+    ) {
+        let mut removed = HashSet::new();
+
+        for (trigger, trigger_zone, trigger_transform, trigger_hitboxes) in &q_zones {
+            for (target, target_zone, target_transform, _) in &q_zones {
+                if trigger == target || removed.contains(&trigger) || removed.contains(&target) {
+                    continue;
+                }
+                let trigger_pos = trigger_transform.translation().truncate();
+                let target_pos = target_transform.translation().truncate();
+
+                let reaction = match recipes.reactions().iter().find(|reaction| {
+                    trigger_zone.tags.contains(&reaction.trigger)
+                        && target_zone.tags.contains(&reaction.target)
+                }) {
+                    Some(v) => v,
+                    None => continue,
+                };
+                if !overlapping(&rapier_ctx, &q_hitboxes, trigger_hitboxes, target) {
+                    continue;
+                }
+                // Each pair of zones only reacts once, however long they overlap
+                if !reacted.insert((trigger, target)) {
+                    continue;
+                }
+
+                if let Some(ingredients) = &reaction.spawn {
+                    let site = match reaction.at {
+                        ReactionSite::Trigger => trigger_pos,
+                        ReactionSite::Target => target_pos,
+                    };
+                    event_writer.send(PotionExplode {
+                        potion_type: PotionType {
+                            contents: ingredients.clone(),
+                            tier: 0,
+                        },
+                        transform: Transform::from_translation(site.extend(0.0)),
+                        velocity: Velocity::default(),
+                    });
+                }
+                if reaction.remove_trigger {
+                    cmd.entity(trigger).despawn_recursive();
+                    removed.insert(trigger);
+                }
+                if reaction.remove_target {
+                    cmd.entity(target).despawn_recursive();
+                    removed.insert(target);
+                }
+            }
+        }
+
+        reacted.retain(|&(trigger, target)| {
+            !removed.contains(&trigger)
+                && !removed.contains(&target)
+                && q_zones.contains(trigger)
+                && q_zones.contains(target)
+        });
+    }
+}
+
+impl bevy::app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(Self::react.run_in_state(GameState::InGame));
+    }
+}
@@ -0,0 +1,720 @@
+use bevy::{
+    ecs::system::EntityCommands,
+    prelude::*,
+    reflect::TypeUuid,
+    sprite::Anchor,
+    utils::{HashMap, HashSet},
+};
+use bevy_rapier2d::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    consts::*,
+    health::{DamageKind, DamageSource},
+    hitbox::{
+        DamageElement, DamageOnce, DamagePeriodic, DirectedForce, Faction, FactionRelations,
+        Falloff, HitSource, Hitbox, Hitstun, RadialForce, RadialImpulse, Single, StatusEffect,
+    },
+    homing::Homing,
+    potion::ThrowPotion,
+    reaction::{PotionZone, Reaction, ZoneTag},
+    ron_asset::{AddRonResource, RonAsset, RonResource},
+    status::{ApplyEffect, Effect},
+    utils::{DespawnTimer, UniformAnim, UniformAnimOnce},
+    Element,
+};
+
+const ELEMENTS: [Element; 5] = [
+    Element::Fire,
+    Element::Water,
+    Element::Wind,
+    Element::Lightning,
+    Element::Earth,
+];
+
+#[derive(Deserialize, Clone, Copy, Default)]
+pub enum SpriteAnchor {
+    #[default]
+    Center,
+    BottomCenter,
+}
+
+impl From<SpriteAnchor> for Anchor {
+    fn from(anchor: SpriteAnchor) -> Self {
+        match anchor {
+            SpriteAnchor::Center => Anchor::Center,
+            SpriteAnchor::BottomCenter => Anchor::BottomCenter,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct SpriteSheet {
+    pub path: String,
+    pub tile_size: (f32, f32),
+    pub columns: usize,
+    pub frame_time: f32,
+    #[serde(default)]
+    pub anchor: SpriteAnchor,
+    /// Stop on the last frame instead of looping
+    #[serde(default)]
+    pub once: bool,
+}
+
+impl SpriteSheet {
+    pub fn atlas(
+        &self,
+        assets: &Res<AssetServer>,
+        atlases: &mut ResMut<Assets<TextureAtlas>>,
+    ) -> Handle<TextureAtlas> {
+        let tex = assets.load(self.path.as_str());
+        atlases.add(TextureAtlas::from_grid(
+            tex,
+            Vec2::new(self.tile_size.0, self.tile_size.1),
+            self.columns,
+            1,
+        ))
+    }
+
+    fn insert_anim(&self, entity: &mut EntityCommands) {
+        if self.once {
+            entity.insert(UniformAnimOnce(Timer::from_seconds(self.frame_time, true)));
+        } else {
+            entity.insert(UniformAnim(Timer::from_seconds(self.frame_time, true)));
+        }
+    }
+}
+
+/// Copies of a sprite laid out along and across the throw direction
+#[derive(Deserialize, Clone)]
+pub struct SpriteGrid {
+    pub sprite: SpriteSheet,
+    pub along: (i32, i32),
+    pub across: (i32, i32),
+    pub spacing: (f32, f32),
+    #[serde(default)]
+    pub across_offset: f32,
+}
+
+#[derive(Deserialize, Clone, Copy)]
+pub enum Shape {
+    Ball(f32),
+    Cuboid(f32, f32),
+}
+
+impl Shape {
+    pub fn collider(&self) -> Collider {
+        match *self {
+            Shape::Ball(radius) => Collider::ball(radius),
+            Shape::Cuboid(half_width, half_height) => Collider::cuboid(half_width, half_height),
+        }
+    }
+
+    /// Distance from the centre to the furthest point of the shape
+    pub fn extent(&self) -> f32 {
+        match *self {
+            Shape::Ball(radius) => radius,
+            Shape::Cuboid(half_width, half_height) => Vec2::new(half_width, half_height).length(),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Default, PartialEq)]
+pub enum HitboxKind {
+    #[default]
+    Attack,
+    Wall,
+}
+
+#[derive(Deserialize, Clone)]
+pub enum HitboxComponent {
+    Hitstun(f32),
+    RadialImpulse {
+        force: f32,
+        #[serde(default)]
+        falloff: Falloff,
+    },
+    RadialForce {
+        force: f32,
+        #[serde(default)]
+        falloff: Falloff,
+    },
+    /// Pushes along the throw direction
+    DirectedForce {
+        force: f32,
+    },
+    DamageOnce {
+        amount: f32,
+        #[serde(default)]
+        falloff: Falloff,
+    },
+    DamagePeriodic {
+        amount: f32,
+        #[serde(default)]
+        falloff: Falloff,
+        period: f32,
+    },
+    StatusEffect(Effect),
+    Single,
+}
+
+impl HitboxComponent {
+    fn insert(&self, entity: &mut EntityCommands, direction: Vec2, amplify: Amplify) {
+        match self {
+            HitboxComponent::Hitstun(duration) => {
+                entity.insert(Hitstun(*duration));
+            }
+            HitboxComponent::RadialImpulse { force, falloff } => {
+                entity.insert(RadialImpulse::new(*force, falloff.clone()));
+            }
+            HitboxComponent::RadialForce { force, falloff } => {
+                entity.insert(RadialForce::new(*force, falloff.clone()));
+            }
+            HitboxComponent::DirectedForce { force } => {
+                entity.insert(DirectedForce::new(direction * *force));
+            }
+            HitboxComponent::DamageOnce { amount, falloff } => {
+                entity.insert(DamageOnce::new(*amount * amplify.damage, falloff.clone()));
+            }
+            HitboxComponent::DamagePeriodic {
+                amount,
+                falloff,
+                period,
+            } => {
+                entity.insert(DamagePeriodic::new(
+                    *amount * amplify.damage,
+                    falloff.clone(),
+                    *period,
+                ));
+            }
+            HitboxComponent::StatusEffect(effect) => {
+                // A delayed explosion's duration is its fuse, so stronger potions mustn't lengthen it
+                let duration = match effect {
+                    Effect::DelayedExplosion => STATUS_EFFECT_DURATION,
+                    _ => STATUS_EFFECT_DURATION * amplify.status,
+                };
+                entity.insert(StatusEffect(ApplyEffect {
+                    effect: effect.clone(),
+                    duration,
+                }));
+            }
+            HitboxComponent::Single => {
+                entity.insert(Single::new());
+            }
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+pub struct HitboxDef {
+    pub shape: Shape,
+    #[serde(default)]
+    pub kind: HitboxKind,
+    /// Distance from the explosion point along the throw direction
+    #[serde(default)]
+    pub offset: f32,
+    /// Rotate the collider to face across the throw direction
+    #[serde(default)]
+    pub aligned: bool,
+    #[serde(default)]
+    pub lifetime: Option<f32>,
+    #[serde(default)]
+    pub components: Vec<HitboxComponent>,
+}
+
+impl HitboxDef {
+    fn spawn(
+        &self,
+        parent: &mut ChildBuilder,
+        direction: Vec2,
+        amplify: Amplify,
+        element: Option<Element>,
+        source: &DamageSource,
+    ) {
+        let rotation = if self.aligned {
+            Quat::from_axis_angle(
+                Vec3::Z,
+                direction.y.atan2(direction.x) + std::f32::consts::PI / 2.0,
+            )
+        } else {
+            Quat::IDENTITY
+        };
+        let mut hitbox = parent.spawn_bundle(SpatialBundle {
+            transform: Transform {
+                rotation,
+                translation: (direction * self.offset).extend(0.0),
+                ..default()
+            },
+            ..default()
+        });
+        hitbox.insert(self.shape.collider());
+
+        match self.kind {
+            HitboxKind::Attack => {
+                hitbox.insert_bundle((
+                    CollisionGroups {
+                        memberships: PLAYER_ATTACK_COLLISION_GROUP,
+                        filters: HITBOX_TARGET_COLLISION_GROUPS,
+                    },
+                    ActiveEvents::COLLISION_EVENTS,
+                    Sensor,
+                    Hitbox,
+                    Faction::Player,
+                ));
+                hitbox.insert(HitSource(source.clone()));
+                if let Some(element) = element {
+                    hitbox.insert(DamageElement(element));
+                }
+            }
+            HitboxKind::Wall => {
+                hitbox.insert(CollisionGroups {
+                    memberships: WALL_COLLISION_GROUP,
+                    filters: ENEMY_COLLISION_GROUP
+                        | PLAYER_COLLISION_GROUP
+                        | PLAYER_ATTACK_COLLISION_GROUP
+                        | ENEMY_ATTACK_COLLISION_GROUP,
+                });
+            }
+        }
+
+        for component in &self.components {
+            component.insert(&mut hitbox, direction, amplify);
+        }
+
+        if let Some(lifetime) = self.lifetime {
+            hitbox.insert(DespawnTimer(Timer::from_seconds(lifetime, false)));
+        }
+    }
+}
+
+/// Projectiles fired outwards in a ring from the explosion point
+#[derive(Deserialize, Clone)]
+pub struct Burst {
+    pub count: u32,
+    pub speed: f32,
+    pub radius: f32,
+    pub lifetime: f32,
+    pub sprite: SpriteSheet,
+    #[serde(default)]
+    pub components: Vec<HitboxComponent>,
+}
+
+#[derive(Deserialize, Clone, Default)]
+pub enum Motion {
+    #[default]
+    Static,
+    /// Keeps travelling in the throw direction
+    Forward { speed: f32 },
+    /// Drifts towards anything it would hurt within `radius`
+    Homing { max_speed: f32, radius: f32 },
+}
+
+/// Multipliers applied to a recipe, once for each ingredient past the first two
+/// and once for each potency tier
+#[derive(Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct Amplify {
+    pub radius: f32,
+    pub damage: f32,
+    pub duration: f32,
+    /// Length of status effects applied by the potion, other than delayed explosions
+    pub status: f32,
+}
+
+impl Default for Amplify {
+    fn default() -> Self {
+        Amplify {
+            radius: 1.0,
+            damage: 1.0,
+            duration: 1.0,
+            status: 1.0,
+        }
+    }
+}
+
+impl Amplify {
+    pub fn powi(&self, level: i32) -> Self {
+        Amplify {
+            radius: self.radius.powi(level),
+            damage: self.damage.powi(level),
+            duration: self.duration.powi(level),
+            status: self.status.powi(level),
+        }
+    }
+}
+
+impl std::ops::Mul for Amplify {
+    type Output = Amplify;
+
+    fn mul(self, rhs: Amplify) -> Amplify {
+        Amplify {
+            radius: self.radius * rhs.radius,
+            damage: self.damage * rhs.damage,
+            duration: self.duration * rhs.duration,
+            status: self.status * rhs.status,
+        }
+    }
+}
+
+/// Temporary shield given to the player if they're close enough when the potion bursts
+#[derive(Deserialize, Clone)]
+pub struct ShieldGrant {
+    pub amount: f32,
+    pub duration: f32,
+    pub radius: f32,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct PotionRecipe {
+    pub ingredients: Vec<Element>,
+    pub name: String,
+    /// Element of the damage the potion deals, physical if unset
+    #[serde(default)]
+    pub element: Option<Element>,
+    /// Shown in the brew wheel, defaults to the effect's own sprite
+    #[serde(default)]
+    pub icon: Option<SpriteSheet>,
+    #[serde(default)]
+    pub sprite: Option<SpriteSheet>,
+    #[serde(default)]
+    pub duration: Option<f32>,
+    #[serde(default)]
+    pub motion: Motion,
+    #[serde(default)]
+    pub hitboxes: Vec<HitboxDef>,
+    #[serde(default)]
+    pub grid: Option<SpriteGrid>,
+    #[serde(default)]
+    pub burst: Option<Burst>,
+    /// How the effect reacts with other live effects
+    #[serde(default)]
+    pub tags: Vec<ZoneTag>,
+    #[serde(default)]
+    pub shield: Option<ShieldGrant>,
+}
+
+impl PotionRecipe {
+    pub fn icon(&self) -> Option<&SpriteSheet> {
+        self.icon
+            .as_ref()
+            .or(self.sprite.as_ref())
+            .or(self.grid.as_ref().map(|grid| &grid.sprite))
+            .or(self.burst.as_ref().map(|burst| &burst.sprite))
+    }
+
+    /// Unamplified radius covered by the potion's hitboxes around the explosion point
+    pub fn radius(&self) -> f32 {
+        self.hitboxes
+            .iter()
+            .map(|hitbox| hitbox.offset.abs() + hitbox.shape.extent())
+            .fold(0.0, f32::max)
+    }
+
+    pub fn spawn(
+        &self,
+        spawned: &mut EntityCommands,
+        assets: &Res<AssetServer>,
+        atlases: &mut ResMut<Assets<TextureAtlas>>,
+        velocity: &Velocity,
+        rotation: f32,
+        amplify: Amplify,
+        relations: &FactionRelations,
+    ) {
+        let direction = velocity.linvel.normalize_or_zero();
+        let source = DamageSource {
+            entity: Some(spawned.id()),
+            kind: DamageKind::Potion(self.name.clone()),
+        };
+
+        if let Some(sprite) = &self.sprite {
+            spawned.insert_bundle((
+                TextureAtlasSprite {
+                    anchor: sprite.anchor.into(),
+                    ..default()
+                },
+                sprite.atlas(assets, atlases),
+            ));
+            sprite.insert_anim(spawned);
+        }
+
+        if let Some(duration) = self.duration {
+            spawned.insert(DespawnTimer(Timer::from_seconds(
+                duration * amplify.duration,
+                false,
+            )));
+        }
+
+        match self.motion {
+            Motion::Static => (),
+            Motion::Forward { speed } => {
+                spawned.insert_bundle((
+                    Velocity {
+                        linvel: direction * speed,
+                        angvel: 0.0,
+                    },
+                    RigidBody::KinematicVelocityBased,
+                ));
+            }
+            Motion::Homing { max_speed, radius } => {
+                spawned.insert_bundle((
+                    Velocity::default(),
+                    RigidBody::KinematicVelocityBased,
+                    Homing { max_speed },
+                    Sensor,
+                    Collider::ball(radius),
+                    ActiveEvents::COLLISION_EVENTS,
+                    CollisionGroups {
+                        memberships: PLAYER_ATTACK_COLLISION_GROUP,
+                        filters: HITBOX_TARGET_COLLISION_GROUPS,
+                    },
+                    Faction::Player,
+                ));
+            }
+        }
+
+        if !self.tags.is_empty() {
+            spawned.insert(PotionZone {
+                tags: self.tags.clone(),
+            });
+        }
+
+        let grid_atlas = self
+            .grid
+            .as_ref()
+            .map(|grid| grid.sprite.atlas(assets, atlases));
+        let burst_atlas = self
+            .burst
+            .as_ref()
+            .map(|burst| burst.sprite.atlas(assets, atlases));
+
+        spawned.with_children(|parent| {
+            for hitbox in &self.hitboxes {
+                hitbox.spawn(parent, direction, amplify, self.element, &source);
+            }
+
+            if let (Some(grid), Some(atlas)) = (&self.grid, grid_atlas) {
+                let across_direction = Vec2::new(-direction.y, direction.x);
+                for i in grid.along.0..=grid.along.1 {
+                    for j in grid.across.0..=grid.across.1 {
+                        let position = direction * grid.spacing.0 * i as f32
+                            + across_direction * (grid.spacing.1 * j as f32 + grid.across_offset);
+                        let mut tile = parent.spawn_bundle(SpriteSheetBundle {
+                            texture_atlas: atlas.clone(),
+                            transform: Transform {
+                                translation: position.extend(-position.y * 0.1e-5),
+                                ..default()
+                            },
+                            ..default()
+                        });
+                        grid.sprite.insert_anim(&mut tile);
+                    }
+                }
+            }
+
+            if let (Some(burst), Some(atlas)) = (&self.burst, burst_atlas) {
+                // Bursts are solid bodies, so they only touch the player when they can hurt them
+                let targets = if relations.hurts(Some(Faction::Player), Faction::Player) {
+                    HITBOX_TARGET_COLLISION_GROUPS
+                } else {
+                    ENEMY_COLLISION_GROUP
+                };
+                for i in 0..burst.count {
+                    let rotation =
+                        rotation + (std::f32::consts::TAU / burst.count as f32) * i as f32;
+                    let mut projectile = parent.spawn_bundle(SpatialBundle::default());
+                    projectile.insert_bundle((
+                        TextureAtlasSprite::default(),
+                        atlas.clone(),
+                        Velocity {
+                            linvel: Vec2::from_angle(rotation) * burst.speed,
+                            angvel: 0.0,
+                        },
+                        RigidBody::Dynamic,
+                        Collider::ball(burst.radius),
+                        CollisionGroups {
+                            memberships: PLAYER_ATTACK_COLLISION_GROUP,
+                            filters: targets | WALL_COLLISION_GROUP,
+                        },
+                        ActiveEvents::COLLISION_EVENTS,
+                        Hitbox,
+                        Faction::Player,
+                        DespawnTimer(Timer::from_seconds(burst.lifetime, false)),
+                        LockedAxes::ROTATION_LOCKED,
+                        Ccd::enabled(),
+                    ));
+                    burst.sprite.insert_anim(&mut projectile);
+                    projectile.insert(HitSource(source.clone()));
+                    if let Some(element) = self.element {
+                        projectile.insert(DamageElement(element));
+                    }
+                    for component in &burst.components {
+                        component.insert(&mut projectile, direction, amplify);
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[derive(Deserialize, TypeUuid)]
+#[uuid = "5d3b8a4e-6c1f-4f0b-9a57-2e8d61c0b7a3"]
+pub struct PotionRecipeList {
+    #[serde(default)]
+    pub amplify: Amplify,
+    /// Multipliers applied once per potency tier
+    #[serde(default)]
+    pub potency: Amplify,
+    pub recipes: Vec<PotionRecipe>,
+    #[serde(default)]
+    pub reactions: Vec<Reaction>,
+}
+
+impl RonAsset for PotionRecipeList {
+    const EXTENSIONS: &'static [&'static str] = &["potions.ron"];
+}
+
+fn recipe_key(ingredients: &[Element]) -> Vec<Element> {
+    let mut key = ingredients.to_vec();
+    key.sort();
+    key
+}
+
+pub struct PotionRecipes {
+    handle: Handle<PotionRecipeList>,
+    amplify: Amplify,
+    potency: Amplify,
+    recipes: HashMap<Vec<Element>, PotionRecipe>,
+    reactions: Vec<Reaction>,
+}
+
+impl PotionRecipes {
+    pub fn get(&self, ingredients: &[Element]) -> Option<&PotionRecipe> {
+        self.recipes.get(&recipe_key(ingredients))
+    }
+
+    /// Finds the recipe for a brew. Brews without a recipe of their own use
+    /// the recipe of their first two ingredients, amplified once for every
+    /// ingredient after that.
+    pub fn resolve(&self, ingredients: &[Element]) -> Option<(&PotionRecipe, Amplify)> {
+        if let Some(recipe) = self.get(ingredients) {
+            return Some((recipe, Amplify::default()));
+        }
+        if ingredients.len() <= 2 {
+            return None;
+        }
+        let recipe = self.get(&ingredients[..2])?;
+        Some((recipe, self.amplify.powi(ingredients.len() as i32 - 2)))
+    }
+
+    /// Multipliers for a potion brewed at the given potency tier
+    pub fn potency(&self, tier: u32) -> Amplify {
+        self.potency.powi(tier as i32)
+    }
+
+    pub fn reactions(&self) -> &[Reaction] {
+        &self.reactions
+    }
+
+    /// Element pairs that have no recipe
+    pub fn missing(&self) -> Vec<(Element, Element)> {
+        let mut missing = vec![];
+        for (i, &e1) in ELEMENTS.iter().enumerate() {
+            for &e2 in &ELEMENTS[i..] {
+                if self.get(&[e1, e2]).is_none() {
+                    missing.push((e1, e2));
+                }
+            }
+        }
+        missing
+    }
+}
+
+/// Ingredient combinations the player has thrown at least once
+#[derive(Default)]
+pub struct DiscoveredPotions(HashSet<Vec<Element>>);
+
+impl DiscoveredPotions {
+    pub fn contains(&self, ingredients: &[Element]) -> bool {
+        self.0.contains(&recipe_key(ingredients))
+    }
+}
+
+impl FromWorld for PotionRecipes {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.resource::<AssetServer>();
+        PotionRecipes {
+            handle: assets.load("potions/default.potions.ron"),
+            amplify: Amplify::default(),
+            potency: Amplify::default(),
+            recipes: HashMap::new(),
+            reactions: Vec::new(),
+        }
+    }
+}
+
+impl RonResource for PotionRecipes {
+    type Asset = PotionRecipeList;
+
+    fn handle(&self) -> &Handle<PotionRecipeList> {
+        &self.handle
+    }
+
+    fn reload(&mut self, list: &PotionRecipeList) {
+        self.amplify = list.amplify;
+        self.potency = list.potency;
+        self.recipes.clear();
+        for recipe in &list.recipes {
+            if recipe.ingredients.len() < 2 {
+                warn!(
+                    "Potion recipe {:?} needs at least two ingredients",
+                    recipe.ingredients
+                );
+                continue;
+            }
+            if self
+                .recipes
+                .insert(recipe_key(&recipe.ingredients), recipe.clone())
+                .is_some()
+            {
+                warn!("Duplicate potion recipe for {:?}", recipe.ingredients);
+            }
+        }
+
+        self.reactions = list.reactions.clone();
+        for reaction in &self.reactions {
+            if let Some(ingredients) = &reaction.spawn {
+                if self.resolve(ingredients).is_none() {
+                    warn!(
+                        "Reaction {:?} + {:?} spawns {:?}, which has no recipe",
+                        reaction.trigger, reaction.target, ingredients
+                    );
+                }
+            }
+        }
+
+        for (e1, e2) in self.missing() {
+            error!("No potion recipe for {:?} + {:?}", e1, e2);
+        }
+    }
+}
+
+pub struct Plugin;
+
+impl Plugin {
+    fn discover(
+        mut event_reader: EventReader<ThrowPotion>,
+        mut discovered: ResMut<DiscoveredPotions>,
+    ) {
+        for event in event_reader.iter() {
+            discovered.0.insert(recipe_key(&event.potion.contents));
+        }
+    }
+}
+
+impl bevy::app::Plugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_ron_resource::<PotionRecipes>()
+            .init_resource::<DiscoveredPotions>()
+            .add_system(Self::discover);
+    }
+}
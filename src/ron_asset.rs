@@ -0,0 +1,83 @@
+use std::marker::PhantomData;
+
+use bevy::{
+    asset::{Asset, AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use serde::de::DeserializeOwned;
+
+/// Data file deserialized straight from RON
+pub trait RonAsset: Asset + DeserializeOwned {
+    /// Double extensions the files use, like `potions.ron`
+    const EXTENSIONS: &'static [&'static str];
+}
+
+/// Resource built from a [`RonAsset`], rebuilt whenever the file loads or is edited
+pub trait RonResource: FromWorld + Send + Sync + 'static {
+    type Asset: RonAsset;
+
+    fn handle(&self) -> &Handle<Self::Asset>;
+
+    fn reload(&mut self, asset: &Self::Asset);
+}
+
+struct RonLoader<T>(PhantomData<fn() -> T>);
+
+impl<T> Default for RonLoader<T> {
+    fn default() -> Self {
+        RonLoader(PhantomData)
+    }
+}
+
+impl<T: RonAsset> AssetLoader for RonLoader<T> {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let asset: T = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(asset));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        T::EXTENSIONS
+    }
+}
+
+fn reload<R: RonResource>(
+    mut event_reader: EventReader<AssetEvent<R::Asset>>,
+    assets: Res<Assets<R::Asset>>,
+    mut resource: ResMut<R>,
+) {
+    for event in event_reader.iter() {
+        match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => {
+                if handle != resource.handle() {
+                    continue;
+                }
+                if let Some(asset) = assets.get(handle) {
+                    resource.reload(asset);
+                }
+            }
+            AssetEvent::Removed { .. } => (),
+        }
+    }
+}
+
+pub trait AddRonResource {
+    /// Registers the resource's asset and loader, and keeps the resource in sync with it
+    fn add_ron_resource<R: RonResource>(&mut self) -> &mut Self;
+}
+
+impl AddRonResource for App {
+    fn add_ron_resource<R: RonResource>(&mut self) -> &mut Self {
+        self.add_asset::<R::Asset>()
+            .init_asset_loader::<RonLoader<R::Asset>>()
+            .init_resource::<R>()
+            .add_system(reload::<R>)
+    }
+}
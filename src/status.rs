@@ -2,16 +2,17 @@ use bevy::prelude::*;
 
 use bevy_rapier2d::prelude::*;
 use iyes_loopless::prelude::*;
+use serde::Deserialize;
 
 use crate::consts::*;
 use crate::health::Dead;
 use crate::hitbox::Hitbox;
 use crate::utils::{DespawnTimer, UniformAnim};
 use crate::{
-    health::HealthChange,
-    hitbox::{DamageOnce, Falloff},
+    health::{DamageKind, DamageSource, HealthChange},
+    hitbox::{DamageElement, DamageOnce, Faction, Falloff, HitSource},
     utils::TimeScale,
-    GameState,
+    Element, GameState,
 };
 
 #[derive(Component)]
@@ -21,9 +22,9 @@ pub struct OnFire {
 }
 
 impl OnFire {
-    pub fn new() -> Self {
+    pub fn new(duration: f32) -> Self {
         OnFire {
-            duration: Timer::from_seconds(5.0, false),
+            duration: Timer::from_seconds(duration, false),
             tick: Timer::from_seconds(0.5, true),
         }
     }
@@ -36,9 +37,9 @@ pub struct Shocked {
 }
 
 impl Shocked {
-    pub fn new() -> Self {
+    pub fn new(duration: f32) -> Self {
         Shocked {
-            duration: Timer::from_seconds(5.0, false),
+            duration: Timer::from_seconds(duration, false),
             tick: Timer::from_seconds(0.5, true),
         }
     }
@@ -50,9 +51,9 @@ pub struct DelayedExplosion {
 }
 
 impl DelayedExplosion {
-    pub fn new() -> Self {
+    pub fn new(duration: f32) -> Self {
         DelayedExplosion {
-            duration: Timer::from_seconds(5.0, false),
+            duration: Timer::from_seconds(duration, false),
         }
     }
 }
@@ -63,9 +64,9 @@ pub struct Blinded {
 }
 
 impl Blinded {
-    pub fn new() -> Self {
+    pub fn new(duration: f32) -> Self {
         Blinded {
-            duration: Timer::from_seconds(5.0, false),
+            duration: Timer::from_seconds(duration, false),
         }
     }
 }
@@ -76,20 +77,55 @@ pub struct Slowed {
 }
 
 impl Slowed {
-    pub fn new() -> Self {
+    pub fn new(duration: f32) -> Self {
         Slowed {
-            duration: Timer::from_seconds(5.0, false),
+            duration: Timer::from_seconds(duration, false),
         }
     }
 }
 
-#[derive(Component, Clone)]
+/// Heals a little every tick
+#[derive(Component)]
+pub struct Regenerating {
+    duration: Timer,
+    tick: Timer,
+    amount: f32,
+}
+
+impl Regenerating {
+    pub fn new(duration: f32) -> Self {
+        Regenerating {
+            duration: Timer::from_seconds(duration, false),
+            tick: Timer::from_seconds(REGENERATING_TICK, true),
+            amount: REGENERATING_TICK_HEAL,
+        }
+    }
+
+    /// Heals `total` in even ticks over `duration`
+    pub fn spread(total: f32, duration: f32) -> Self {
+        let ticks = (duration / REGENERATING_TICK).floor().max(1.0);
+        Regenerating {
+            amount: total / ticks,
+            ..Regenerating::new(duration)
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
 pub enum Effect {
     OnFire,
     Shocked,
     DelayedExplosion,
     Blinded,
     Slowed,
+    Regenerating,
+}
+
+/// Status effect waiting to be applied to whatever it was inserted on
+#[derive(Component, Clone)]
+pub struct ApplyEffect {
+    pub effect: Effect,
+    pub duration: f32,
 }
 
 #[derive(Component)]
@@ -110,25 +146,29 @@ pub struct SlowedEffect;
 pub struct Plugin;
 
 impl Plugin {
-    fn insert_effect(mut cmd: Commands, affected: Query<(Entity, &Effect)>) {
-        for (entity, effect) in &affected {
+    fn insert_effect(mut cmd: Commands, affected: Query<(Entity, &ApplyEffect)>) {
+        for (entity, apply) in &affected {
             let mut entity = cmd.entity(entity);
-            entity.remove::<Effect>();
-            match effect {
+            entity.remove::<ApplyEffect>();
+            let duration = apply.duration;
+            match apply.effect {
                 Effect::OnFire => {
-                    entity.insert(OnFire::new());
+                    entity.insert(OnFire::new(duration));
                 }
                 Effect::DelayedExplosion => {
-                    entity.insert(DelayedExplosion::new());
+                    entity.insert(DelayedExplosion::new(duration));
                 }
                 Effect::Shocked => {
-                    entity.insert(Shocked::new());
+                    entity.insert(Shocked::new(duration));
                 }
                 Effect::Blinded => {
-                    entity.insert(Blinded::new());
+                    entity.insert(Blinded::new(duration));
                 }
                 Effect::Slowed => {
-                    entity.insert(Slowed::new());
+                    entity.insert(Slowed::new(duration));
+                }
+                Effect::Regenerating => {
+                    entity.insert(Regenerating::new(duration));
                 }
             }
         }
@@ -150,6 +190,13 @@ impl Plugin {
                 event_writer.send(HealthChange {
                     target: entity,
                     amount: -15.0,
+                    element: Some(Element::Fire),
+                    source: Some(DamageSource {
+                        entity: None,
+                        kind: DamageKind::StatusTick(Effect::OnFire),
+                    }),
+                    ignore_iframes: false,
+                    hit: None,
                 });
             }
 
@@ -159,6 +206,35 @@ impl Plugin {
         }
     }
 
+    fn tick_regenerating(
+        mut cmd: Commands,
+        mut q_regenerating: Query<(Entity, &mut Regenerating)>,
+        time: Res<Time>,
+        time_scale: Res<TimeScale>,
+        mut event_writer: EventWriter<HealthChange>,
+    ) {
+        let delta = time.delta().mul_f32(**time_scale);
+        for (entity, mut regenerating) in &mut q_regenerating {
+            regenerating.duration.tick(delta);
+            regenerating.tick.tick(delta);
+
+            if regenerating.tick.finished() {
+                event_writer.send(HealthChange {
+                    target: entity,
+                    amount: regenerating.amount,
+                    element: None,
+                    source: None,
+                    ignore_iframes: false,
+                    hit: None,
+                });
+            }
+
+            if regenerating.duration.finished() {
+                cmd.entity(entity).remove::<Regenerating>();
+            }
+        }
+    }
+
     fn tick_shocked(
         mut cmd: Commands,
         mut q_shocked: Query<(Entity, &GlobalTransform, &mut Shocked)>,
@@ -176,11 +252,17 @@ impl Plugin {
                         Collider::ball(24.0),
                         CollisionGroups {
                             memberships: PLAYER_ATTACK_COLLISION_GROUP,
-                            filters: ENEMY_COLLISION_GROUP,
+                            filters: HITBOX_TARGET_COLLISION_GROUPS,
                         },
                         ActiveEvents::COLLISION_EVENTS,
                         Sensor,
                         Hitbox,
+                        Faction::Player,
+                        DamageElement(Element::Lightning),
+                        HitSource(DamageSource {
+                            entity: None,
+                            kind: DamageKind::StatusTick(Effect::Shocked),
+                        }),
                         DamageOnce::new(5.0, Falloff::none()),
                         DespawnTimer(Timer::from_seconds(0.05, false)),
                     ));
@@ -231,11 +313,17 @@ impl Plugin {
                                 Collider::ball(24.0),
                                 CollisionGroups {
                                     memberships: PLAYER_ATTACK_COLLISION_GROUP,
-                                    filters: ENEMY_COLLISION_GROUP,
+                                    filters: HITBOX_TARGET_COLLISION_GROUPS,
                                 },
                                 ActiveEvents::COLLISION_EVENTS,
                                 Sensor,
                                 Hitbox,
+                                Faction::Player,
+                                DamageElement(Element::Fire),
+                                HitSource(DamageSource {
+                                    entity: None,
+                                    kind: DamageKind::StatusTick(Effect::DelayedExplosion),
+                                }),
                                 DamageOnce::new(160.0, Falloff::none()),
                                 DespawnTimer(Timer::from_seconds(0.05, false)),
                             ));
@@ -386,6 +474,7 @@ impl bevy::app::Plugin for Plugin {
             .add_system(Self::tick_delayed_explosion.run_in_state(GameState::InGame))
             .add_system(Self::tick_blinded.run_in_state(GameState::InGame))
             .add_system(Self::tick_slowed.run_in_state(GameState::InGame))
+            .add_system(Self::tick_regenerating.run_in_state(GameState::InGame))
             .add_system_to_stage(
                 CoreStage::PostUpdate,
                 Self::remove_visuals::<OnFire, OnFireEffect>.run_in_state(GameState::InGame),
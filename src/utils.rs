@@ -4,7 +4,12 @@ use bevy::{prelude::*, render::camera::RenderTarget};
 
 use bevy_rapier2d::prelude::*;
 
-use crate::{consts::*, player::Player, MainCamera};
+use crate::{
+    consts::*,
+    hitbox::{Faction, FactionRelations},
+    player::Player,
+    MainCamera,
+};
 
 #[derive(Default, Deref, DerefMut, Debug)]
 pub struct MousePosition(pub Vec3);
@@ -130,18 +135,31 @@ impl Plugin {
             }
         }
     }
+    /// Projectiles with a faction pass through targets their faction wouldn't hurt,
+    /// like the enemy that fired them
     fn destroy_on_hit(
         mut cmd: Commands,
         mut event_reader: EventReader<CollisionEvent>,
-        q_destroy_on_hit: Query<(), With<DestroyOnHit>>,
+        q_destroy_on_hit: Query<Option<&Faction>, With<DestroyOnHit>>,
+        q_faction: Query<&Faction>,
+        relations: Res<FactionRelations>,
     ) {
+        let passes_through =
+            |faction: Option<&Faction>, other: Entity| match (faction, q_faction.get(other)) {
+                (Some(faction), Ok(target)) => !relations.hurts(Some(*faction), *target),
+                _ => false,
+            };
         for event in event_reader.iter() {
             match event {
                 CollisionEvent::Started(e1, e2, _) => {
-                    if let Ok(_) = q_destroy_on_hit.get(*e1) {
-                        cmd.entity(*e1).despawn_recursive();
-                    } else if let Ok(_) = q_destroy_on_hit.get(*e2) {
-                        cmd.entity(*e2).despawn_recursive();
+                    if let Ok(faction) = q_destroy_on_hit.get(*e1) {
+                        if !passes_through(faction, *e2) {
+                            cmd.entity(*e1).despawn_recursive();
+                        }
+                    } else if let Ok(faction) = q_destroy_on_hit.get(*e2) {
+                        if !passes_through(faction, *e1) {
+                            cmd.entity(*e2).despawn_recursive();
+                        }
                     } else {
                         continue;
                     }